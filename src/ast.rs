@@ -0,0 +1,325 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::config::Associativity;
+use crate::error::Span;
+use crate::lexer::Ops;
+use crate::literal::Literal;
+use crate::parser::{get_associativity, get_precedence};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Plus,
+    Minus,
+    /// `!b`, logical not of a boolean.
+    Not,
+    /// `~n`, which flips every bit of an integer, so it is `-n - 1`.
+    BitNot,
+    /// `√x`, the square root, as computed by `sqrt`.
+    Sqrt,
+    /// Postfix `n!`.
+    Factorial,
+    /// Postfix `n!!`, the product of every second number down from `n`.
+    DoubleFactorial,
+}
+/// Expression tree produced by `math_parse`.
+///
+/// The `span` of a `Unary` or `Binary` node is the span of its operator
+/// token, or the gap between the operands for an implicit product such as
+/// `2x`; a `Group` spans its parentheses.
+#[derive(Debug, PartialEq)]
+pub enum Expr {
+    Number {
+        value: Literal,
+        span: Span,
+    },
+    /// Imaginary literal such as `2i`; only valid in `Mode::Complex`.
+    Imaginary {
+        value: Literal,
+        span: Span,
+    },
+    /// Percent literal such as `15%`; `value` is the number as written.
+    Percent {
+        value: Literal,
+        span: Span,
+    },
+    /// `true` or `false`.
+    Bool {
+        value: bool,
+        span: Span,
+    },
+    Variable {
+        name: String,
+        span: Span,
+    },
+    /// `name(args)`; `span` runs from the name to the closing parenthesis.
+    Call {
+        name: String,
+        args: Vec<Expr>,
+        span: Span,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+        span: Span,
+    },
+    Binary {
+        op: Ops,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+        span: Span,
+    },
+    /// `if condition then a else b` or `condition ? a : b`. `span` covers
+    /// the `if` or `?`.
+    Conditional {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+        span: Span,
+    },
+    Group {
+        inner: Box<Expr>,
+        span: Span,
+    },
+}
+/// A line of input: a bare expression, `name = expr` or a function
+/// definition `name(params) = expr`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Expr(Expr),
+    /// `span` covers the variable name.
+    Assign {
+        name: String,
+        span: Span,
+        value: Expr,
+    },
+    /// `span` covers the function name.
+    Define {
+        name: String,
+        params: Vec<String>,
+        body: Expr,
+        span: Span,
+    },
+}
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Number { span, .. }
+            | Expr::Imaginary { span, .. }
+            | Expr::Percent { span, .. }
+            | Expr::Bool { span, .. }
+            | Expr::Variable { span, .. }
+            | Expr::Call { span, .. }
+            | Expr::Unary { span, .. }
+            | Expr::Binary { span, .. }
+            | Expr::Conditional { span, .. }
+            | Expr::Group { span, .. } => *span,
+        }
+    }
+    /// The `Binary` and `Unary` nodes from `self` down through each left
+    /// operand, outermost first, and the operand they bottom out at. The
+    /// parser builds a chain such as `1 + 1 + ... + 1` as deep as it is
+    /// long, so whatever walks a whole tree follows chains with a loop
+    /// rather than recursing once per operator.
+    pub(crate) fn chain(&self) -> (Vec<&Expr>, &Expr) {
+        let mut links = Vec::new();
+        let mut expr = self;
+        while let Expr::Binary { lhs: next, .. } | Expr::Unary { operand: next, .. } = expr {
+            links.push(expr);
+            expr = next;
+        }
+        (links, expr)
+    }
+}
+impl Clone for Expr {
+    fn clone(&self) -> Self {
+        let (links, first) = self.chain();
+        let first = match first {
+            Expr::Number { value, span } => Expr::Number {
+                value: value.clone(),
+                span: *span,
+            },
+            Expr::Imaginary { value, span } => Expr::Imaginary {
+                value: value.clone(),
+                span: *span,
+            },
+            Expr::Percent { value, span } => Expr::Percent {
+                value: value.clone(),
+                span: *span,
+            },
+            Expr::Bool { value, span } => Expr::Bool {
+                value: *value,
+                span: *span,
+            },
+            Expr::Variable { name, span } => Expr::Variable {
+                name: name.clone(),
+                span: *span,
+            },
+            Expr::Call { name, args, span } => Expr::Call {
+                name: name.clone(),
+                args: args.clone(),
+                span: *span,
+            },
+            Expr::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => Expr::Conditional {
+                condition: condition.clone(),
+                then_branch: then_branch.clone(),
+                else_branch: else_branch.clone(),
+                span: *span,
+            },
+            Expr::Group { inner, span } => Expr::Group {
+                inner: inner.clone(),
+                span: *span,
+            },
+            Expr::Unary { .. } | Expr::Binary { .. } => unreachable!("chains end in an operand"),
+        };
+        links
+            .into_iter()
+            .rev()
+            .fold(first, |operand, link| match link {
+                Expr::Unary { op, span, .. } => Expr::Unary {
+                    op: *op,
+                    operand: Box::new(operand),
+                    span: *span,
+                },
+                Expr::Binary { op, rhs, span, .. } => Expr::Binary {
+                    op: *op,
+                    lhs: Box::new(operand),
+                    rhs: rhs.clone(),
+                    span: *span,
+                },
+                _ => unreachable!("chains only link unary and binary nodes"),
+            })
+    }
+}
+/// Takes chains apart one link at a time, for the same reason as `chain`.
+impl Drop for Expr {
+    fn drop(&mut self) {
+        let leaf = || Expr::Bool {
+            value: false,
+            span: Span::new(0, 0),
+        };
+        let (Expr::Binary { lhs: next, .. } | Expr::Unary { operand: next, .. }) = self else {
+            return;
+        };
+        let mut next = std::mem::replace(&mut **next, leaf());
+        while let Expr::Binary { lhs: link, .. } | Expr::Unary { operand: link, .. } = &mut next {
+            next = std::mem::replace(&mut **link, leaf());
+        }
+    }
+}
+/// Prints the expression back as source text. The parentheses that were
+/// written are printed, and so are any that precedence alone would not
+/// reproduce, such as around the tight implicit product of `6/2x`, which
+/// prints as `6 / (2 * x)`. The output parses back to the same tree, apart
+/// from a `Group` for each added pair of parentheses.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number { value, .. } => value.fmt(f),
+            Expr::Imaginary { value, .. } => write!(f, "{}i", value),
+            Expr::Percent { value, .. } => write!(f, "{}%", value),
+            Expr::Bool { value, .. } => value.fmt(f),
+            Expr::Variable { name, .. } => f.write_str(name),
+            Expr::Call { name, args, .. } => {
+                write!(f, "{}(", name)?;
+                for (idx, arg) in args.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(", ")?;
+                    }
+                    arg.fmt(f)?;
+                }
+                f.write_str(")")
+            }
+            // a chain prints its prefix operators outermost first, then its
+            // innermost operand, then the operators that follow it
+            Expr::Unary { .. } | Expr::Binary { .. } => {
+                let (links, first) = self.chain();
+                for link in &links {
+                    if let Expr::Unary { op, .. } = link {
+                        f.write_str(match op {
+                            UnaryOp::Plus => "+",
+                            UnaryOp::Minus => "-",
+                            UnaryOp::Not => "!",
+                            UnaryOp::BitNot => "~",
+                            UnaryOp::Sqrt => "√",
+                            UnaryOp::Factorial | UnaryOp::DoubleFactorial => "",
+                        })?;
+                    }
+                    if wraps_operand(link) {
+                        f.write_str("(")?;
+                    }
+                }
+                first.fmt(f)?;
+                for link in links.iter().rev() {
+                    if wraps_operand(link) {
+                        f.write_str(")")?;
+                    }
+                    match link {
+                        Expr::Binary { op, rhs, .. } if wraps(*op, rhs, true) => {
+                            write!(f, " {} ({})", op, rhs)?
+                        }
+                        Expr::Binary { op, rhs, .. } => write!(f, " {} {}", op, rhs)?,
+                        Expr::Unary {
+                            op: UnaryOp::Factorial,
+                            ..
+                        } => f.write_str("!")?,
+                        Expr::Unary {
+                            op: UnaryOp::DoubleFactorial,
+                            ..
+                        } => f.write_str("!!")?,
+                        _ => {}
+                    }
+                }
+                Ok(())
+            }
+            // ternaries print in the keyword form, which parses the same way
+            Expr::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => write!(
+                f,
+                "if {} then {} else {}",
+                condition, then_branch, else_branch
+            ),
+            Expr::Group { inner, .. } => write!(f, "({})", inner),
+        }
+    }
+}
+/// Whether the first operand of a chain link needs parentheses to parse
+/// back as that operand.
+fn wraps_operand(link: &Expr) -> bool {
+    match link {
+        Expr::Binary { op, lhs, .. } => wraps(*op, lhs, false),
+        // only `^` binds tighter than a prefix operator, and nothing binds
+        // tighter than a postfix one
+        Expr::Unary {
+            op: UnaryOp::Factorial | UnaryOp::DoubleFactorial,
+            operand,
+            ..
+        } => matches!(**operand, Expr::Binary { .. }),
+        Expr::Unary { operand, .. } => {
+            matches!(**operand, Expr::Binary { op, .. } if op != Ops::Exponent)
+        }
+        _ => false,
+    }
+}
+/// Whether `operand`, the left or `right` operand of `op`, needs
+/// parentheses: a binary node binding looser than `op`, or as loosely on
+/// the side `op` does not associate towards by default.
+fn wraps(op: Ops, operand: &Expr, right: bool) -> bool {
+    let Expr::Binary { op: inner, .. } = operand else {
+        return false;
+    };
+    match get_precedence(inner).cmp(&get_precedence(&op)) {
+        Ordering::Less => true,
+        Ordering::Equal => right == (get_associativity(&op) == Associativity::Left),
+        Ordering::Greater => false,
+    }
+}
@@ -0,0 +1,646 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// Each limb holds nine decimal digits, which keeps parsing and printing
+/// trivial and lets limb products fit in a u64.
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: usize = 9;
+/// Refuse to build integers with more decimal digits than this, rather than
+/// grinding away on them.
+pub(crate) const MAX_DIGITS: f64 = 100_000.0;
+
+/// Arbitrary-precision signed integer stored as little-endian base 10^9 limbs.
+///
+/// Zero has no limbs and is never negative.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt {
+            negative: false,
+            limbs: Vec::new(),
+        }
+    }
+    fn from_parts(negative: bool, mut limbs: Vec<u32>) -> BigInt {
+        trim(&mut limbs);
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+    /// Parses a run of ASCII decimal digits. Returns `None` for an empty
+    /// string or any non-digit character.
+    pub fn parse_digits(digits: &str) -> Option<BigInt> {
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mut limbs = Vec::with_capacity(digits.len() / BASE_DIGITS + 1);
+        let mut end = digits.len();
+        while end > 0 {
+            let start = end.saturating_sub(BASE_DIGITS);
+            limbs.push(digits[start..end].parse::<u32>().ok()?);
+            end = start;
+        }
+        Some(BigInt::from_parts(false, limbs))
+    }
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+    pub fn abs(&self) -> BigInt {
+        BigInt {
+            negative: false,
+            limbs: self.limbs.clone(),
+        }
+    }
+    pub fn to_i64(&self) -> Option<i64> {
+        let mut magnitude: i128 = 0;
+        for limb in self.limbs.iter().rev() {
+            magnitude = magnitude * BASE as i128 + *limb as i128;
+            if magnitude > i64::MAX as i128 + 1 {
+                return None;
+            }
+        }
+        let value = if self.negative { -magnitude } else { magnitude };
+        i64::try_from(value).ok()
+    }
+    /// Base-10 logarithm of the magnitude, accurate enough to estimate the
+    /// size of a result before computing it. Zero yields negative infinity.
+    pub fn log10_abs(&self) -> f64 {
+        match self.limbs.last() {
+            None => f64::NEG_INFINITY,
+            Some(top) => {
+                let next = if self.limbs.len() > 1 {
+                    self.limbs[self.limbs.len() - 2] as f64 / BASE as f64
+                } else {
+                    0.0
+                };
+                (*top as f64 + next).log10() + ((self.limbs.len() - 1) * BASE_DIGITS) as f64
+            }
+        }
+    }
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+    /// Greatest common divisor of the magnitudes; `gcd(0, 0)` is 0.
+    ///
+    /// Lehmer's algorithm: the steps of Euclid's algorithm are run on the
+    /// top two limbs of both numbers for as long as they provably match the
+    /// steps on the whole numbers, and only their combined effect is applied
+    /// to the whole numbers.
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let (mut a, mut b) = (self.abs(), other.abs());
+        if a < b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        while !b.is_zero() {
+            let (x, y, z, w) = lehmer_step(&a.limbs, &b.limbs);
+            if y == 0 {
+                let (_, remainder) = a.div_rem(&b).expect("nonzero divisor");
+                a = b;
+                b = remainder;
+            } else {
+                let next_a = &(&BigInt::from(x) * &a) + &(&BigInt::from(y) * &b);
+                b = &(&BigInt::from(z) * &a) + &(&BigInt::from(w) * &b);
+                a = next_a;
+            }
+        }
+        a
+    }
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut acc = BigInt::from(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = &acc * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        acc
+    }
+    /// Floor of the square root, or `None` for negative numbers.
+    pub fn isqrt(&self) -> Option<BigInt> {
+        if self.negative {
+            return None;
+        }
+        if self.is_zero() {
+            return Some(BigInt::zero());
+        }
+        // Newton's method from a starting point above the root; the
+        // iterates decrease until they reach the floor of the root. Past a
+        // few limbs the start is one more than the root of the top half of
+        // the limbs, shifted back, which already has half the digits right.
+        let two = BigInt::from(2);
+        let quarter = self.limbs.len() / 4;
+        let mut root = if quarter == 0 {
+            BigInt::from(10).pow((self.log10_abs() / 2.0) as u32 + 1)
+        } else {
+            let top = BigInt::from_parts(false, self.limbs[2 * quarter..].to_vec());
+            let top_root = &top.isqrt().expect("nonnegative") + &BigInt::from(1);
+            let mut limbs = vec![0; quarter];
+            limbs.extend(top_root.limbs);
+            BigInt::from_parts(false, limbs)
+        };
+        loop {
+            let (quotient, _) = self.div_rem(&root).expect("nonzero root");
+            let (next, _) = (&root + &quotient).div_rem(&two).expect("nonzero divisor");
+            if next >= root {
+                return Some(root);
+            }
+            root = next;
+        }
+    }
+    /// Bitwise and, treating both operands as two's complement numbers
+    /// sign-extended to any width, like `i64`'s `&`.
+    pub fn bitand(&self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a & b)
+    }
+    pub fn bitor(&self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a | b)
+    }
+    pub fn bitxor(&self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+    fn bitwise(&self, other: &BigInt, op: fn(u32, u32) -> u32) -> BigInt {
+        let (left, right) = (self.to_binary(), other.to_binary());
+        // one spare limb keeps room for the sign bit
+        let width = left.len().max(right.len()) + 1;
+        let left = twos_complement(self.negative, left, width);
+        let right = twos_complement(other.negative, right, width);
+        let mut bits: Vec<u32> = left.iter().zip(&right).map(|(a, b)| op(*a, *b)).collect();
+        let negative = bits.last().is_some_and(|top| top >> 31 == 1);
+        if negative {
+            negate_bits(&mut bits);
+        }
+        BigInt::from_binary(negative, &bits)
+    }
+    /// Formats the magnitude in base 2, 8 or 16 with uppercase digits and a
+    /// leading `-` if negative, but no prefix.
+    pub fn to_string_radix(&self, radix: u32) -> String {
+        let group = match radix {
+            2 => 1,
+            8 => 3,
+            16 => 4,
+            _ => panic!("unsupported radix {}", radix),
+        };
+        let mut bits = String::new();
+        for limb in self.to_binary().iter().rev() {
+            bits.push_str(&format!("{:032b}", limb));
+        }
+        let bits = bits.trim_start_matches('0').as_bytes();
+        let mut digits = Vec::new();
+        for chunk in bits.rchunks(group) {
+            let digit = chunk
+                .iter()
+                .fold(0, |acc, bit| acc * 2 + u32::from(*bit - b'0'));
+            digits.push(char::from_digit(digit, radix).expect("digit below radix"));
+        }
+        if digits.is_empty() {
+            digits.push('0');
+        }
+        let sign = if self.negative { "-" } else { "" };
+        format!(
+            "{}{}",
+            sign,
+            digits.iter().rev().collect::<String>().to_uppercase()
+        )
+    }
+    /// Little-endian base 2^32 digits of the magnitude.
+    fn to_binary(&self) -> Vec<u32> {
+        let mut decimal = self.limbs.clone();
+        let mut binary = Vec::new();
+        while !decimal.is_empty() {
+            let mut remainder: u64 = 0;
+            for limb in decimal.iter_mut().rev() {
+                let current = remainder * BASE + *limb as u64;
+                *limb = (current >> 32) as u32;
+                remainder = current & 0xFFFF_FFFF;
+            }
+            binary.push(remainder as u32);
+            trim(&mut decimal);
+        }
+        binary
+    }
+    fn from_binary(negative: bool, binary: &[u32]) -> BigInt {
+        let mut limbs: Vec<u32> = Vec::new();
+        for digit in binary.iter().rev() {
+            let mut carry = *digit as u64;
+            for limb in limbs.iter_mut() {
+                let current = ((*limb as u64) << 32) + carry;
+                *limb = (current % BASE) as u32;
+                carry = current / BASE;
+            }
+            while carry > 0 {
+                limbs.push((carry % BASE) as u32);
+                carry /= BASE;
+            }
+        }
+        BigInt::from_parts(negative, limbs)
+    }
+    /// Truncating division, matching `i64`'s `/` and `%`: the quotient rounds
+    /// toward zero and the remainder takes the sign of `self`. Returns `None`
+    /// when `divisor` is zero.
+    pub fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.limbs, &divisor.limbs);
+        Some((
+            BigInt::from_parts(self.negative != divisor.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+}
+impl From<i64> for BigInt {
+    fn from(value: i64) -> BigInt {
+        let mut magnitude = value.unsigned_abs();
+        let mut limbs = Vec::new();
+        while magnitude > 0 {
+            limbs.push((magnitude % BASE) as u32);
+            magnitude /= BASE;
+        }
+        BigInt::from_parts(value < 0, limbs)
+    }
+}
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut digits = String::new();
+        match self.limbs.split_last() {
+            None => digits.push('0'),
+            Some((top, rest)) => {
+                digits.push_str(&top.to_string());
+                for limb in rest.iter().rev() {
+                    digits.push_str(&format!("{:09}", limb));
+                }
+            }
+        }
+        f.pad_integral(!self.negative, "", &digits)
+    }
+}
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.limbs, &other.limbs),
+            (true, true) => cmp_magnitude(&other.limbs, &self.limbs),
+        }
+    }
+}
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.limbs.clone())
+    }
+}
+impl Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.limbs, &other.limbs));
+        }
+        match cmp_magnitude(&self.limbs, &other.limbs) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_magnitude(&other.limbs, &self.limbs))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.limbs, &other.limbs)),
+        }
+    }
+}
+impl Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+impl Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_magnitude(&self.limbs, &other.limbs),
+        )
+    }
+}
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        out.push((sum % BASE) as u32);
+        carry = sum / BASE;
+    }
+    if carry > 0 {
+        out.push(carry as u32);
+    }
+    out
+}
+/// Requires `|a| >= |b|`.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, limb) in a.iter().enumerate() {
+        let mut diff = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += BASE as i64;
+            borrow = 1;
+        }
+        out.push(diff as u32);
+    }
+    out
+}
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![0u64; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let cur = out[i + j] + *x as u64 * *y as u64 + carry;
+            out[i + j] = cur % BASE;
+            carry = cur / BASE;
+        }
+        out[i + b.len()] += carry;
+    }
+    out.into_iter().map(|limb| limb as u32).collect()
+}
+/// Pads a base 2^32 magnitude to `width` limbs, negated if `negative`.
+fn twos_complement(negative: bool, mut bits: Vec<u32>, width: usize) -> Vec<u32> {
+    bits.resize(width, 0);
+    if negative {
+        negate_bits(&mut bits);
+    }
+    bits
+}
+/// Two's complement negation in place: invert and add one.
+fn negate_bits(bits: &mut [u32]) {
+    let mut carry = true;
+    for bit in bits.iter_mut() {
+        let (sum, overflow) = (!*bit).overflowing_add(u32::from(carry));
+        *bit = sum;
+        carry = overflow;
+    }
+}
+fn trim(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+/// The cofactors `(x, y, z, w)` that take `(a, b)`, with `a >= b`, to
+/// `(x*a + y*b, z*a + w*b)` after as many steps of Euclid's algorithm as the
+/// top two limbs of `a`, and the same limbs of `b`, determine (Knuth's
+/// algorithm L). `y` is 0 when not even one step is certain.
+fn lehmer_step(a: &[u32], b: &[u32]) -> (i64, i64, i64, i64) {
+    if a.len() < 3 {
+        return (1, 0, 0, 1);
+    }
+    let top = |limbs: &[u32]| {
+        let limb = |idx: usize| *limbs.get(idx).unwrap_or(&0) as i128;
+        limb(a.len() - 1) * BASE as i128 + limb(a.len() - 2)
+    };
+    let (mut a_top, mut b_top) = (top(a), top(b));
+    let (mut x, mut y, mut z, mut w) = (1i128, 0i128, 0i128, 1i128);
+    while b_top + z != 0 && b_top + w != 0 {
+        let quotient = (a_top + x) / (b_top + z);
+        if quotient != (a_top + y) / (b_top + w) {
+            break;
+        }
+        (x, z) = (z, x - quotient * z);
+        (y, w) = (w, y - quotient * w);
+        (a_top, b_top) = (b_top, a_top - quotient * b_top);
+    }
+    let small = |value: i128| i64::try_from(value).expect("cofactors stay below the top limbs");
+    (small(x), small(y), small(z), small(w))
+}
+/// Long division, one base 10^9 digit at a time (Knuth's algorithm D).
+/// Both operands are first scaled so the divisor's top limb is at least
+/// half the base; each quotient digit estimated from the top two limbs is
+/// then at most two too large, and is corrected before it is used.
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if let [divisor] = b {
+        let (quotient, remainder) = div_rem_small(a, *divisor);
+        return (quotient, vec![remainder]);
+    }
+    let scale = (BASE / (*b.last().expect("nonzero divisor") as u64 + 1)) as u32;
+    let v = mul_scaled(b, scale);
+    let mut u = mul_scaled(a, scale);
+    u.resize(a.len() + 1, 0);
+    let n = v.len();
+    let (top, second) = (v[n - 1] as u64, v[n - 2] as u64);
+    let mut quotient = vec![0u32; a.len() - n + 1];
+    for j in (0..quotient.len()).rev() {
+        let numerator = u[j + n] as u64 * BASE + u[j + n - 1] as u64;
+        let mut guess = numerator / top;
+        let mut rest = numerator % top;
+        while guess >= BASE || guess * second > rest * BASE + u[j + n - 2] as u64 {
+            guess -= 1;
+            rest += top;
+            if rest >= BASE {
+                break;
+            }
+        }
+        // subtract guess * v from the window of u it lines up with
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..=n {
+            let product = guess * *v.get(i).unwrap_or(&0) as u64 + carry;
+            carry = product / BASE;
+            let diff = u[j + i] as i64 - (product % BASE) as i64 - borrow;
+            borrow = i64::from(diff < 0);
+            u[j + i] = (diff + borrow * BASE as i64) as u32;
+        }
+        // the guess was one too large: add v back
+        if borrow != 0 {
+            guess -= 1;
+            let mut carry = 0u64;
+            for i in 0..=n {
+                let sum = u[j + i] as u64 + *v.get(i).unwrap_or(&0) as u64 + carry;
+                u[j + i] = (sum % BASE) as u32;
+                carry = sum / BASE;
+            }
+        }
+        quotient[j] = guess as u32;
+    }
+    u.truncate(n);
+    trim(&mut u);
+    let (remainder, _) = div_rem_small(&u, scale);
+    trim(&mut quotient);
+    (quotient, remainder)
+}
+/// `a * factor` for a single limb `factor`, without trimming.
+fn mul_scaled(a: &[u32], factor: u32) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0u64;
+    for limb in a {
+        let product = *limb as u64 * factor as u64 + carry;
+        out.push((product % BASE) as u32);
+        carry = product / BASE;
+    }
+    if carry > 0 {
+        out.push(carry as u32);
+    }
+    out
+}
+/// Division by a single nonzero limb.
+fn div_rem_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for (i, limb) in a.iter().enumerate().rev() {
+        let current = remainder * BASE + *limb as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    trim(&mut quotient);
+    (quotient, remainder as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn matches_i128_arithmetic() {
+        // xorshift keeps the operands deterministic without a rand dependency
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state as i64) >> (state % 60)
+        };
+        for _ in 0..2000 {
+            let (a, b) = (next(), next());
+            let (big_a, big_b) = (BigInt::from(a), BigInt::from(b));
+            let (a, b) = (a as i128, b as i128);
+            assert!((&big_a + &big_b).to_string() == (a + b).to_string());
+            assert!((&big_a - &big_b).to_string() == (a - b).to_string());
+            assert!((&big_a * &big_b).to_string() == (a * b).to_string());
+            assert!(big_a.cmp(&big_b) == a.cmp(&b));
+            // squares reach past 64 bits, so several limbs take part
+            let (wide_a, wide_b) = (&big_a * &big_a, -&(&big_b * &big_b));
+            let (wide, narrow) = (a * a, -(b * b));
+            assert!(wide_a.bitand(&wide_b).to_string() == (wide & narrow).to_string());
+            assert!(wide_a.bitor(&wide_b).to_string() == (wide | narrow).to_string());
+            assert!(wide_a.bitxor(&wide_b).to_string() == (wide ^ narrow).to_string());
+            assert!(big_a.bitand(&big_b).to_string() == (a & b).to_string());
+            assert!(wide_a.to_string_radix(16) == format!("{:X}", wide));
+            assert!(wide_a.to_string_radix(8) == format!("{:o}", wide));
+            let sign = if a < 0 { "-" } else { "" };
+            assert!(big_a.to_string_radix(2) == format!("{}{:b}", sign, a.unsigned_abs()));
+            let root = big_a.abs().isqrt().unwrap();
+            let next = &root + &BigInt::from(1);
+            assert!(&root * &root <= big_a.abs() && &next * &next > big_a.abs());
+            if b != 0 {
+                let (quotient, remainder) = (&big_a * &big_a).div_rem(&big_b).unwrap();
+                assert!(quotient.to_string() == (a * a / b).to_string());
+                assert!(remainder.to_string() == (a * a % b).to_string());
+            }
+        }
+    }
+    #[test]
+    fn division_of_many_limb_numbers() {
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut next = |limit: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % limit
+        };
+        // runs of 0 and 999999999 limbs exercise the corrections of the
+        // quotient estimate
+        let mut limbs = |len: u64| -> Vec<u32> {
+            (0..len)
+                .map(|_| match next(4) {
+                    0 => 0,
+                    1 => (BASE - 1) as u32,
+                    _ => next(BASE) as u32,
+                })
+                .collect()
+        };
+        for len in 0..300 {
+            let a = BigInt::from_parts(false, limbs(len % 30 + 1));
+            let b = BigInt::from_parts(len % 3 == 0, limbs(len % 12 + 1));
+            if b.is_zero() {
+                continue;
+            }
+            let (quotient, remainder) = a.div_rem(&b).unwrap();
+            assert!(&(&quotient * &b) + &remainder == a);
+            assert!(!remainder.is_negative() && remainder < b.abs());
+        }
+    }
+    #[test]
+    fn gcd_of_many_limb_numbers() {
+        let (two, three) = (BigInt::from(2), BigInt::from(3));
+        let factor = &BigInt::from(10).pow(40) + &BigInt::from(3);
+        for exp in [1, 30, 300, 1000] {
+            let (a, b) = (two.pow(exp), three.pow(exp));
+            assert!(a.gcd(&b) == BigInt::from(1));
+            assert!((&a * &factor).gcd(&(&b * &factor)) == factor);
+            assert!((&a * &b).gcd(&a) == a);
+            assert!((-&a).gcd(&BigInt::zero()) == a);
+        }
+        // consecutive Fibonacci numbers take the most steps
+        let (mut fib, mut next) = (BigInt::from(1), BigInt::from(1));
+        for _ in 0..2000 {
+            (fib, next) = (next.clone(), &fib + &next);
+        }
+        assert!(fib.gcd(&next) == BigInt::from(1));
+        assert!((&fib * &factor).gcd(&(&next * &factor)) == factor);
+    }
+    #[test]
+    fn square_roots_of_many_limb_numbers() {
+        for digits in [17, 36, 37, 100, 1001, 4000] {
+            let square = BigInt::from(10).pow(digits);
+            for value in [
+                &square - &BigInt::from(1),
+                square.clone(),
+                &square + &BigInt::from(7),
+            ] {
+                let root = value.isqrt().unwrap();
+                let next = &root + &BigInt::from(1);
+                assert!(&root * &root <= value && &next * &next > value);
+            }
+        }
+    }
+    #[test]
+    fn parse_and_print_round_trip() {
+        for digits in [
+            "0",
+            "7",
+            "1000000000",
+            "999999999999999999",
+            "100000000000000000000001",
+        ] {
+            assert!(BigInt::parse_digits(digits).unwrap().to_string() == digits);
+        }
+        assert!(BigInt::parse_digits("000").unwrap().is_zero());
+        assert!(BigInt::parse_digits("12a").is_none());
+        assert!(BigInt::from(i64::MIN).to_i64() == Some(i64::MIN));
+        assert!((&BigInt::from(i64::MAX) + &BigInt::from(1))
+            .to_i64()
+            .is_none());
+    }
+}
@@ -0,0 +1,117 @@
+/// Complex number with `f64` parts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+    pub fn from_polar(modulus: f64, argument: f64) -> Complex {
+        Complex::new(modulus * argument.cos(), modulus * argument.sin())
+    }
+    pub fn is_zero(&self) -> bool {
+        self.re == 0.0 && self.im == 0.0
+    }
+    /// The modulus `|z|`.
+    pub fn abs(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+    /// The argument in `(-pi, pi]`.
+    pub fn arg(&self) -> f64 {
+        // adding 0.0 turns a -0.0 left by negation into 0.0, so -2 has an
+        // argument of pi rather than -pi
+        (self.im + 0.0).atan2(self.re)
+    }
+    pub fn add(&self, other: &Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+    pub fn sub(&self, other: &Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+    pub fn mul(&self, other: &Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+    /// Returns `None` when `other` is zero.
+    pub fn div(&self, other: &Complex) -> Option<Complex> {
+        if other.is_zero() {
+            return None;
+        }
+        let denom = other.re * other.re + other.im * other.im;
+        Some(Complex::new(
+            (self.re * other.re + self.im * other.im) / denom,
+            (self.im * other.re - self.re * other.im) / denom,
+        ))
+    }
+    pub fn neg(&self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+    /// Principal square root, exact on the negative real axis so
+    /// `sqrt(-4)` is `2i` rather than `1.2e-16+2i`.
+    pub fn sqrt(&self) -> Complex {
+        if self.im == 0.0 {
+            return if self.re >= 0.0 {
+                Complex::new(self.re.sqrt(), 0.0)
+            } else {
+                Complex::new(0.0, (-self.re).sqrt())
+            };
+        }
+        let modulus = self.abs();
+        let re = ((modulus + self.re) / 2.0).sqrt();
+        let im = ((modulus - self.re) / 2.0).sqrt().copysign(self.im);
+        Complex::new(re, im)
+    }
+    pub fn exp(&self) -> Complex {
+        Complex::from_polar(self.re.exp(), self.im)
+    }
+    /// Principal natural logarithm.
+    pub fn ln(&self) -> Complex {
+        Complex::new(self.abs().ln(), self.arg())
+    }
+    pub fn sin(&self) -> Complex {
+        Complex::new(
+            self.re.sin() * self.im.cosh(),
+            self.re.cos() * self.im.sinh(),
+        )
+    }
+    pub fn cos(&self) -> Complex {
+        Complex::new(
+            self.re.cos() * self.im.cosh(),
+            -self.re.sin() * self.im.sinh(),
+        )
+    }
+    /// Returns `None` where the cosine is zero.
+    pub fn tan(&self) -> Option<Complex> {
+        self.sin().div(&self.cos())
+    }
+    /// Principal power. Small integer exponents use repeated multiplication
+    /// so results like `i^2` come out exact.
+    pub fn pow(&self, exp: &Complex) -> Option<Complex> {
+        if exp.im == 0.0 && exp.re.fract() == 0.0 && exp.re.abs() <= 64.0 {
+            let mut acc = Complex::new(1.0, 0.0);
+            for _ in 0..exp.re.abs() as u32 {
+                acc = acc.mul(self);
+            }
+            return if exp.re < 0.0 {
+                Complex::new(1.0, 0.0).div(&acc)
+            } else {
+                Some(acc)
+            };
+        }
+        if exp.im == 0.0 && exp.re == 0.5 {
+            return Some(self.sqrt());
+        }
+        if self.is_zero() {
+            return if exp.re > 0.0 {
+                Some(Complex::new(0.0, 0.0))
+            } else {
+                None
+            };
+        }
+        Some(exp.mul(&self.ln()).exp())
+    }
+}
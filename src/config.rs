@@ -0,0 +1,135 @@
+use crate::lexer::Ops;
+use crate::parser::get_associativity;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+/// What to do when an integer operation leaves the `i64` range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Report `EvalError::Overflow`.
+    #[default]
+    Checked,
+    /// Wrap around using two's complement arithmetic.
+    Wrapping,
+    /// Clamp to `i64::MIN` / `i64::MAX`.
+    Saturating,
+}
+/// Numeric type used for literals and arithmetic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// 64-bit signed integers, see `Overflow`.
+    #[default]
+    Integer,
+    /// Arbitrary-precision integers.
+    BigInt,
+    /// IEEE 754 double precision floats.
+    Float,
+    /// Exact fractions of arbitrary-precision integers.
+    Rational,
+    /// Complex numbers with `f64` parts; enables `i` and `j` literals.
+    Complex,
+    /// Base-10 fixed point, see `DecimalConfig`.
+    Decimal,
+}
+/// How `Mode::Decimal` results are rounded to their scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Ties go to the even neighbour, so `0.125` becomes `0.12`.
+    #[default]
+    HalfEven,
+    /// Ties go away from zero, so `0.125` becomes `0.13`.
+    HalfUp,
+    /// Drop extra digits, rounding toward zero.
+    Truncate,
+}
+/// Settings for `Mode::Decimal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalConfig {
+    /// Digits kept after the decimal point.
+    pub scale: u32,
+    pub rounding: Rounding,
+}
+impl Default for DecimalConfig {
+    fn default() -> DecimalConfig {
+        DecimalConfig {
+            scale: 2,
+            rounding: Rounding::default(),
+        }
+    }
+}
+/// How juxtaposition such as `2x`, `3(4+5)` or `(a)(b)` multiplies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImplicitMultiplication {
+    /// Binds like `*`, so `6/2(1+2)` is `(6/2)*(1+2)`, which is 9.
+    #[default]
+    AsMultiply,
+    /// Binds tighter than `*` and `/` but looser than `^`, so `6/2(1+2)` is
+    /// `6/(2*(1+2))`, which is 1.
+    Tight,
+    /// Rejects juxtaposition with `ParseError::ExpectedOperator`.
+    Disabled,
+}
+/// What a percent literal such as `15%` means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Percent {
+    /// Like a desk calculator: `a + b%` is `a * (1 + b/100)` and `a - b%` is
+    /// `a * (1 - b/100)`, so `200 + 15%` is 230. Elsewhere `b%` is `b/100`.
+    #[default]
+    Calculator,
+    /// `b%` is always `b/100`, so `200 + 15%` is 200.15.
+    Fraction,
+}
+/// How deep user-defined functions may call each other by default.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 100;
+
+/// Options that change how expressions are parsed and evaluated.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Operators whose associativity differs from `get_associativity`.
+    pub associativity: Vec<(Ops, Associativity)>,
+    pub overflow: Overflow,
+    pub mode: Mode,
+    pub decimal: DecimalConfig,
+    pub implicit_multiplication: ImplicitMultiplication,
+    pub percent: Percent,
+    /// Rejects the Unicode spellings such as `×`, `√` and `x²` with
+    /// `LexError::UnknownToken`, leaving only ASCII input.
+    pub strict_ascii: bool,
+    /// Calls to user-defined functions nested deeper than this fail with
+    /// `EvalError::RecursionLimit` instead of overflowing the stack, as do
+    /// fewer calls whose bodies nest deeper than `MAX_EVAL_DEPTH` in total.
+    pub max_call_depth: usize,
+}
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            associativity: Vec::new(),
+            overflow: Overflow::default(),
+            mode: Mode::default(),
+            decimal: DecimalConfig::default(),
+            implicit_multiplication: ImplicitMultiplication::default(),
+            percent: Percent::default(),
+            strict_ascii: false,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+        }
+    }
+}
+impl Config {
+    pub fn associativity(&self, op: &Ops) -> Associativity {
+        self.associativity
+            .iter()
+            .rev()
+            .find(|(overridden, _)| overridden == op)
+            .map_or_else(|| get_associativity(op), |(_, assoc)| *assoc)
+    }
+    /// Overrides the associativity of `op`, e.g. `Associativity::Left` for
+    /// `Ops::Exponent` to match calculators that fold `2^3^2` as `(2^3)^2`.
+    pub fn set_associativity(&mut self, op: Ops, assoc: Associativity) {
+        self.associativity
+            .retain(|(overridden, _)| *overridden != op);
+        self.associativity.push((op, assoc));
+    }
+}
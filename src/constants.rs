@@ -0,0 +1,32 @@
+use crate::bigint::BigInt;
+use crate::literal::Literal;
+
+/// Built-in constants, correctly rounded to 50 decimal places, which is more
+/// than any mode that can hold them needs.
+const CONSTANTS: &[(&str, &str)] = &[
+    ("pi", "3.14159265358979323846264338327950288419716939937511"),
+    ("e", "2.71828182845904523536028747135266249775724709369996"),
+    (
+        "tau",
+        "6.28318530717958647692528676655900576839433879875021",
+    ),
+    (
+        "phi",
+        "1.61803398874989484820458683436563811772030917980576",
+    ),
+];
+pub(crate) fn builtin_constant(name: &str) -> Option<Literal> {
+    let (_, text) = CONSTANTS.iter().find(|(constant, _)| *constant == name)?;
+    let (whole, frac) = text
+        .split_once('.')
+        .expect("constants are written with a fraction");
+    Some(Literal {
+        digits: BigInt::parse_digits(&format!("{}{}", whole, frac)).expect("ascii digits"),
+        exponent: -(frac.len() as i64),
+    })
+}
+/// Names of the built-in constants, in the order they are listed in the
+/// README.
+pub fn constant_names() -> impl Iterator<Item = &'static str> {
+    CONSTANTS.iter().map(|(name, _)| *name)
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::ast::{Expr, Statement};
+use crate::config::Config;
+use crate::environment::Environment;
+use crate::error::{Error, EvalError};
+use crate::eval::{evaluate_statement, execute_in, Scope};
+use crate::functions::Arity;
+use crate::lexer::math_lexer_with;
+use crate::parser::math_parse_statement;
+use crate::value::Value;
+
+/// Error type host functions return. Any error converts into it with `?`.
+pub type BoxError = Box<dyn error::Error + Send + Sync>;
+
+type Callback = dyn Fn(&[Value]) -> Result<Value, BoxError> + Send + Sync;
+
+#[derive(Clone)]
+pub(crate) struct HostFunction {
+    pub arity: Arity,
+    pub call: Arc<Callback>,
+}
+impl HostFunction {
+    /// Runs the callback, turning an `EvalError` it returns back into that
+    /// kind and wrapping anything else in `EvalError::Host`.
+    pub fn call(&self, args: &[Value]) -> Result<Value, EvalError> {
+        (self.call)(args).map_err(|error| match error.downcast::<EvalError>() {
+            Ok(kind) => *kind,
+            Err(error) => EvalError::Host(HostError(Arc::from(error))),
+        })
+    }
+}
+pub(crate) type HostFunctions = BTreeMap<String, HostFunction>;
+
+/// An error returned by a host function, kept so the embedder can get its
+/// own error type back with `downcast_ref`.
+#[derive(Debug, Clone)]
+pub struct HostError(Arc<dyn error::Error + Send + Sync>);
+impl HostError {
+    pub fn get(&self) -> &(dyn error::Error + Send + Sync + 'static) {
+        &*self.0
+    }
+    pub fn downcast_ref<T: error::Error + 'static>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+/// Two host errors are equal only if they are the same error value.
+impl PartialEq for HostError {
+    fn eq(&self, other: &HostError) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+impl Eq for HostError {}
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Everything an embedder needs to evaluate expressions: the `Config`, the
+/// variables assigned so far and any functions registered from Rust.
+///
+/// ```
+/// use math_expression::{Arity, Context, Value};
+///
+/// let mut context = Context::new();
+/// context.register("tax", Arity::exact(1), |args| match &args[0] {
+///     Value::Int(amount) => Ok(Value::Int(amount * 20 / 100)),
+///     _ => Err("tax expects an integer amount".into()),
+/// });
+/// assert_eq!(context.evaluate("tax(150) + 1").unwrap(), Value::Int(31));
+/// ```
+#[derive(Clone, Default)]
+pub struct Context {
+    pub config: Config,
+    pub env: Environment,
+    functions: HostFunctions,
+}
+impl Context {
+    pub fn new() -> Context {
+        Context::default()
+    }
+    pub fn with_config(config: Config) -> Context {
+        Context {
+            config,
+            ..Context::default()
+        }
+    }
+    /// Makes `function` callable as `name(args)`. Host functions take
+    /// precedence over built-ins of the same name but not over functions
+    /// defined in the expression language, and registering a name again
+    /// replaces the earlier function.
+    ///
+    /// Calls with an argument count `arity` rejects never reach `function`.
+    /// Returning an `EvalError` reports that error kind; any other error is
+    /// reported as `EvalError::Host`.
+    pub fn register<F>(&mut self, name: &str, arity: Arity, function: F)
+    where
+        F: Fn(&[Value]) -> Result<Value, BoxError> + Send + Sync + 'static,
+    {
+        let function = HostFunction {
+            arity,
+            call: Arc::new(function),
+        };
+        self.functions.insert(name.to_string(), function);
+    }
+    /// Returns whether a function called `name` was registered.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.functions.remove(name).is_some()
+    }
+    /// Names of the registered host functions, in order.
+    pub fn functions(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+    /// Lexes, parses and runs `input`, which may be an assignment. A
+    /// function definition is `ParseError::UnexpectedDefinition`; use `run`
+    /// for those.
+    pub fn evaluate(&mut self, input: &str) -> Result<Value, Error> {
+        let tokens = math_lexer_with(input, &self.config)?;
+        let statement = math_parse_statement(&tokens, &self.config)?;
+        evaluate_statement(&statement, &self.config, &mut self.env, &self.functions)
+    }
+    /// Like `evaluate`, but `input` may also define a function, which
+    /// returns `None`.
+    pub fn run(&mut self, input: &str) -> Result<Option<Value>, Error> {
+        let tokens = math_lexer_with(input, &self.config)?;
+        let statement = math_parse_statement(&tokens, &self.config)?;
+        self.execute(&statement)
+    }
+    /// Evaluates `expr` against this context. This is what
+    /// `traverse_expr_tree` does without host functions or variables.
+    pub fn traverse(&self, expr: &Expr) -> Result<Value, Error> {
+        self.scope().eval(expr)
+    }
+    pub fn execute(&mut self, statement: &Statement) -> Result<Option<Value>, Error> {
+        execute_in(statement, &self.config, &mut self.env, &self.functions)
+    }
+    fn scope(&self) -> Scope<'_> {
+        Scope::new(&self.config, &self.env, &self.functions)
+    }
+}
+impl fmt::Debug for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("config", &self.config)
+            .field("env", &self.env)
+            .field("functions", &self.functions.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
@@ -0,0 +1,91 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::bigint::{BigInt, MAX_DIGITS};
+use crate::config::Rounding;
+use crate::error::EvalError;
+use crate::rational::Rational;
+
+/// Base-10 fixed-point number, `unscaled / 10^scale`.
+///
+/// Results are computed exactly and then rounded to the configured scale, so
+/// `0.1 + 0.2` is exactly `0.30` at scale 2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decimal {
+    unscaled: BigInt,
+    scale: u32,
+}
+impl Decimal {
+    pub fn new(unscaled: BigInt, scale: u32) -> Decimal {
+        Decimal { unscaled, scale }
+    }
+    pub fn unscaled(&self) -> &BigInt {
+        &self.unscaled
+    }
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+    /// Rounds `value` to `scale` decimal places.
+    pub fn from_rational(
+        value: &Rational,
+        scale: u32,
+        rounding: Rounding,
+    ) -> Result<Decimal, EvalError> {
+        if scale as f64 > MAX_DIGITS {
+            return Err(EvalError::Overflow);
+        }
+        let scaled = value.numer() * &BigInt::from(10).pow(scale);
+        let (quotient, remainder) = scaled.div_rem(value.denom()).expect("nonzero denominator");
+        let away = match rounding {
+            _ if remainder.is_zero() => false,
+            Rounding::Truncate => false,
+            Rounding::HalfUp | Rounding::HalfEven => {
+                let twice = &remainder.abs() * &BigInt::from(2);
+                match twice.cmp(value.denom()) {
+                    Ordering::Less => false,
+                    Ordering::Greater => true,
+                    Ordering::Equal => rounding == Rounding::HalfUp || !is_even(&quotient),
+                }
+            }
+        };
+        let unscaled = match away {
+            false => quotient,
+            true if scaled.is_negative() => &quotient - &BigInt::from(1),
+            true => &quotient + &BigInt::from(1),
+        };
+        Ok(Decimal { unscaled, scale })
+    }
+    pub fn to_rational(&self) -> Rational {
+        Rational::new(self.unscaled.clone(), BigInt::from(10).pow(self.scale))
+            .expect("nonzero scale")
+    }
+    pub fn to_f64(&self) -> f64 {
+        format!("{}e-{}", self.unscaled, self.scale)
+            .parse()
+            .unwrap_or(f64::NAN)
+    }
+}
+fn is_even(value: &BigInt) -> bool {
+    let (_, parity) = value.div_rem(&BigInt::from(2)).expect("nonzero divisor");
+    parity.is_zero()
+}
+/// Prints every digit of the scale, so `0.3` at scale 2 is `0.30`.
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = self.scale as usize;
+        let digits = format!(
+            "{:0>width$}",
+            self.unscaled.abs().to_string(),
+            width = scale + 1
+        );
+        let (whole, frac) = digits.split_at(digits.len() - scale);
+        if self.unscaled.is_negative() {
+            f.write_str("-")?;
+        }
+        f.write_str(whole)?;
+        if !frac.is_empty() {
+            write!(f, ".{}", frac)?;
+        }
+        Ok(())
+    }
+}
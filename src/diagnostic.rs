@@ -0,0 +1,135 @@
+use std::borrow::Cow;
+
+use crate::bigint::MAX_DIGITS;
+use crate::error::{Error, EvalError, LexError, ParseError};
+
+/// Renders `error` against the `input` it was produced from, underlining the
+/// offending span:
+///
+/// ```text
+/// error: expected operator.
+///   1 + 2 3
+///         ^ unexpected token
+///   hint: did you forget an operator?
+/// ```
+pub fn render_diagnostic(input: &str, error: &Error) -> String {
+    let span = error.span();
+    let start = span.start.min(input.len());
+    let end = span.end.clamp(start, input.len());
+    // keep tabs so the underline lines up with the echoed input
+    let padding: String = input[..start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let width = input[start..end].chars().count().max(1);
+    let mut out = format!("error: {}\n  {}\n  {}^", error, input, padding);
+    out.push_str(&"~".repeat(width - 1));
+    out.push(' ');
+    out.push_str(label(error));
+    if let Some(hint) = hint(error) {
+        out.push_str("\n  hint: ");
+        out.push_str(&hint);
+    }
+    out
+}
+fn label(error: &Error) -> &'static str {
+    match error {
+        Error::Lex(kind, _) => match kind {
+            LexError::UnknownToken => "not recognized",
+            LexError::LeadingZero => "leading zero",
+            LexError::MalformedNumber => "incomplete number",
+        },
+        Error::Parse(kind, _) => match kind {
+            ParseError::ExpectedRightOperand => "operand missing here",
+            ParseError::ExpectedOperator => "unexpected token",
+            ParseError::ExpectedCloseParenth => "expected ')' here",
+            ParseError::UnexpectedCloseParenth => "no matching '('",
+            ParseError::NotUnaryOperator => "used as unary operator",
+            ParseError::EmptyExpression => "nothing to evaluate",
+            ParseError::UnexpectedAssign => "not an assignment",
+            ParseError::UnexpectedComma => "outside a function call",
+            ParseError::DuplicateParameter => "already a parameter",
+            ParseError::UnexpectedDefinition => "defines a function",
+            ParseError::ExpectedThen => "expected 'then' here",
+            ParseError::ExpectedElse => "expected 'else' here",
+            ParseError::ExpectedColon => "expected ':' here",
+            ParseError::UnmatchedBranch => "no matching 'if' or '?'",
+            ParseError::NestingLimit => "too deep",
+        },
+        Error::Eval(kind, _) => match kind {
+            EvalError::DivideByZero => "divisor is zero",
+            EvalError::NegativeExponent => "exponent is negative",
+            EvalError::NegativeFactorial => "operand is negative",
+            EvalError::NegativeShift => "shift is negative",
+            EvalError::ExpectedNumber => "needs numbers",
+            EvalError::ExpectedBool => "needs booleans",
+            EvalError::Overflow => "result is too large",
+            EvalError::NotAnInteger => "not an integer",
+            EvalError::ImaginaryNumber => "imaginary",
+            EvalError::UndefinedVariable => "not defined",
+            EvalError::UnknownFunction => "not a function",
+            EvalError::WrongArity { .. } => "wrong number of arguments",
+            EvalError::NotExact => "not exact",
+            EvalError::NotReal => "not real",
+            EvalError::Domain => "out of domain",
+            EvalError::RecursionLimit => "too deep",
+            EvalError::ConstantAssignment => "constant",
+            EvalError::Host(_) => "failed here",
+        },
+    }
+}
+fn hint(error: &Error) -> Option<Cow<'static, str>> {
+    let hint = match error {
+        Error::Lex(kind, _) => match kind {
+            LexError::UnknownToken => Some(
+                "only numbers, names, parentheses and operators such as + - * / ^ & are allowed",
+            ),
+            LexError::LeadingZero => Some("remove the leading zeroes"),
+            LexError::MalformedNumber => Some("write numbers as 1.5, .5, 1.5e-3, 1_000 or 0xFF"),
+        },
+        Error::Parse(kind, _) => match kind {
+            ParseError::ExpectedRightOperand => Some("add a number after the operator"),
+            ParseError::ExpectedOperator => Some("did you forget an operator?"),
+            ParseError::ExpectedCloseParenth => Some("every '(' needs a matching ')'"),
+            ParseError::UnexpectedCloseParenth => Some("remove the ')' or add a matching '('"),
+            ParseError::NotUnaryOperator => Some("only +, -, ~ and ! can prefix an operand"),
+            ParseError::EmptyExpression => None,
+            ParseError::UnexpectedAssign => Some("assign with `name = expression`"),
+            ParseError::UnexpectedComma => Some("commas separate arguments, as in `max(1, 2)`"),
+            ParseError::DuplicateParameter => Some("give each parameter a different name"),
+            ParseError::UnexpectedDefinition => Some("define functions with `Context::run`"),
+            ParseError::ExpectedThen | ParseError::ExpectedElse | ParseError::UnmatchedBranch => {
+                Some("write conditionals as `if x > 0 then x else -x`")
+            }
+            ParseError::ExpectedColon => Some("write ternaries as `x > 0 ? x : -x`"),
+            ParseError::NestingLimit => Some("split the expression or remove some parentheses"),
+        },
+        Error::Eval(kind, _) => match kind {
+            EvalError::Overflow => {
+                return Some(Cow::Owned(format!(
+                    "integer mode holds 64-bit values; big integer, rational and decimal \
+                     values may have up to {} digits",
+                    MAX_DIGITS
+                )))
+            }
+            EvalError::NotAnInteger => Some("float mode supports fractional numbers"),
+            EvalError::ImaginaryNumber => None,
+            EvalError::UndefinedVariable => Some("assign it first, e.g. `rate = 7`"),
+            EvalError::NotExact => Some("float mode supports irrational results"),
+            EvalError::RecursionLimit => Some("check that the recursion stops"),
+            EvalError::ConstantAssignment => Some("pick another name, e.g. `p = 3`"),
+            EvalError::ExpectedBool => Some("compare the number first, e.g. `x != 0`"),
+            EvalError::UnknownFunction
+            | EvalError::WrongArity { .. }
+            | EvalError::NotReal
+            | EvalError::Domain
+            | EvalError::ExpectedNumber
+            | EvalError::Host(_) => None,
+            EvalError::DivideByZero
+            | EvalError::NegativeExponent
+            | EvalError::NegativeFactorial
+            | EvalError::NegativeShift => None,
+        },
+    };
+    hint.map(Cow::Borrowed)
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use std::fmt;
+
+use crate::ast::Expr;
+use crate::value::Value;
+
+/// A function defined in the expression language, e.g. `f(x, y) = x^2 + y`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub params: Vec<String>,
+    pub body: Expr,
+}
+/// Prints the parameter list and body, `(x, y) = x ^ 2 + y`.
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}) = {}", self.params.join(", "), self.body)
+    }
+}
+/// Variables, constants and function definitions that persist between
+/// evaluations, such as the REPL session.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    variables: BTreeMap<String, Value>,
+    constants: BTreeMap<String, Value>,
+    functions: BTreeMap<String, Function>,
+}
+impl Environment {
+    pub fn new() -> Environment {
+        Environment::default()
+    }
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.variables.get(name)
+    }
+    /// Binds `name` to `value`, replacing any previous binding.
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.variables.insert(name.to_string(), value);
+    }
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.variables.remove(name)
+    }
+    /// Variables in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.variables
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+    pub fn constant(&self, name: &str) -> Option<&Value> {
+        self.constants.get(name)
+    }
+    /// Adds a read-only constant alongside the built-in ones, replacing any
+    /// variable or earlier constant called `name`. Constants shadow the
+    /// built-in constants, and assigning to them is an error.
+    pub fn set_constant(&mut self, name: &str, value: Value) {
+        self.variables.remove(name);
+        self.constants.insert(name.to_string(), value);
+    }
+    pub fn remove_constant(&mut self, name: &str) -> Option<Value> {
+        self.constants.remove(name)
+    }
+    /// Constants added with `set_constant`, in name order.
+    pub fn constants(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.constants
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name)
+    }
+    /// Defines `name`, replacing any previous definition.
+    pub fn define(&mut self, name: &str, function: Function) {
+        self.functions.insert(name.to_string(), function);
+    }
+    pub fn undefine(&mut self, name: &str) -> Option<Function> {
+        self.functions.remove(name)
+    }
+    /// Function definitions in name order.
+    pub fn functions(&self) -> impl Iterator<Item = (&str, &Function)> {
+        self.functions
+            .iter()
+            .map(|(name, function)| (name.as_str(), function))
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+
+use crate::context::HostError;
+use crate::functions::Arity;
+
+/// Byte range `start..end` into the source string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexError {
+    UnknownToken,
+    LeadingZero,
+    MalformedNumber,
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    ExpectedRightOperand,
+    ExpectedOperator,
+    ExpectedCloseParenth,
+    UnexpectedCloseParenth,
+    NotUnaryOperator,
+    EmptyExpression,
+    UnexpectedAssign,
+    UnexpectedComma,
+    DuplicateParameter,
+    /// A function definition given to an entry point that must produce a
+    /// value, such as `Context::evaluate`.
+    UnexpectedDefinition,
+    ExpectedThen,
+    ExpectedElse,
+    ExpectedColon,
+    /// `then`, `else` or `:` without an `if` or `?` before it.
+    UnmatchedBranch,
+    /// Nesting deeper than `MAX_NESTING`, e.g. a thousand `(`.
+    NestingLimit,
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    DivideByZero,
+    NegativeExponent,
+    NegativeFactorial,
+    NegativeShift,
+    /// A boolean used where a number is needed, e.g. `(1 < 2) + 1`.
+    ExpectedNumber,
+    /// A number used where a boolean is needed, e.g. `1 && true`.
+    ExpectedBool,
+    Overflow,
+    NotAnInteger,
+    ImaginaryNumber,
+    UndefinedVariable,
+    UnknownFunction,
+    WrongArity {
+        expected: Arity,
+        found: usize,
+    },
+    /// The result cannot be represented exactly in an integer or rational
+    /// mode, e.g. `sqrt(2)`.
+    NotExact,
+    NotReal,
+    /// The argument is outside the function's domain, e.g. `sqrt(-1)` in
+    /// integer mode.
+    Domain,
+    /// Assignment to a built-in or embedder constant such as `pi`.
+    ConstantAssignment,
+    /// User-defined functions nested deeper than `Config::max_call_depth`,
+    /// or whose bodies nest deeper than `MAX_EVAL_DEPTH` between them.
+    RecursionLimit,
+    /// Returned by a function registered on a `Context`.
+    Host(HostError),
+}
+/// Error returned by any stage of the pipeline, tagged with the span of the
+/// offending input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Lex(LexError, Span),
+    Parse(ParseError, Span),
+    Eval(EvalError, Span),
+}
+impl Error {
+    pub fn span(&self) -> Span {
+        match self {
+            Error::Lex(_, span) | Error::Parse(_, span) | Error::Eval(_, span) => *span,
+        }
+    }
+}
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LexError::UnknownToken => "Unknown token",
+            LexError::LeadingZero => "numbers cannot have leading zeroes",
+            LexError::MalformedNumber => "malformed number",
+        })
+    }
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ParseError::ExpectedRightOperand => "expected right operand.",
+            ParseError::ExpectedOperator => "expected operator.",
+            ParseError::ExpectedCloseParenth => "Expected ')'",
+            ParseError::UnexpectedCloseParenth => "Unexpected ')'",
+            ParseError::NotUnaryOperator => "Multiplication or Division is not a unary operator.",
+            ParseError::EmptyExpression => "empty expression",
+            ParseError::UnexpectedAssign => "unexpected '='",
+            ParseError::UnexpectedComma => "unexpected ','",
+            ParseError::DuplicateParameter => "duplicate parameter name",
+            ParseError::UnexpectedDefinition => "unexpected function definition",
+            ParseError::ExpectedThen => "expected 'then'",
+            ParseError::ExpectedElse => "expected 'else'",
+            ParseError::ExpectedColon => "expected ':'",
+            ParseError::UnmatchedBranch => "branch without a condition",
+            ParseError::NestingLimit => "expression is nested too deeply",
+        })
+    }
+}
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::WrongArity { expected, found } => {
+                return write!(f, "expected {}, found {}", expected, found)
+            }
+            EvalError::Host(error) => return error.fmt(f),
+            _ => {}
+        }
+        f.write_str(match self {
+            EvalError::DivideByZero => "cannot divide by zero",
+            EvalError::NegativeExponent => "cannot do power to negative numbers",
+            EvalError::NegativeFactorial => "cannot take the factorial of a negative number",
+            EvalError::NegativeShift => "cannot shift by a negative amount",
+            EvalError::ExpectedNumber => "expected a number, found a boolean",
+            EvalError::ExpectedBool => "expected a boolean, found a number",
+            EvalError::Overflow => "arithmetic overflow",
+            EvalError::NotAnInteger => "expected an integer",
+            EvalError::ImaginaryNumber => "imaginary numbers need complex mode",
+            EvalError::UndefinedVariable => "undefined variable",
+            EvalError::UnknownFunction => "unknown function",
+            EvalError::WrongArity { .. } | EvalError::Host(_) => unreachable!("formatted above"),
+            EvalError::NotExact => "result is not exact in this mode",
+            EvalError::NotReal => "expected a real number",
+            EvalError::Domain => "argument is outside the function's domain",
+            EvalError::RecursionLimit => "recursion limit reached",
+            EvalError::ConstantAssignment => "cannot assign to a constant",
+        })
+    }
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Lex(kind, _) => kind.fmt(f),
+            Error::Parse(kind, _) => kind.fmt(f),
+            Error::Eval(kind, _) => kind.fmt(f),
+        }
+    }
+}
+impl std::error::Error for EvalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EvalError::Host(error) => Some(error.get()),
+            _ => None,
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Eval(kind, _) => kind.source(),
+            _ => None,
+        }
+    }
+}
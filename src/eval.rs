@@ -0,0 +1,877 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+use crate::ast::{Expr, Statement, UnaryOp};
+use crate::bigint::{BigInt, MAX_DIGITS};
+use crate::complex::Complex;
+use crate::config::{Config, DecimalConfig, Mode, Overflow, Percent};
+use crate::constants::builtin_constant;
+use crate::context::{HostFunction, HostFunctions};
+use crate::decimal::Decimal;
+use crate::environment::{Environment, Function};
+use crate::error::{Error, EvalError, ParseError, Span};
+use crate::functions::{builtin, sqrt, Arity, Builtin};
+use crate::lexer::Ops;
+use crate::literal::Literal;
+use crate::rational::Rational;
+use crate::value::Value;
+
+pub fn traverse_expr_tree(expr: &Expr) -> Result<Value, Error> {
+    traverse_expr_tree_with(expr, &Config::default())
+}
+/// Like `traverse_expr_tree`, but honours the mode and overflow settings in
+/// `config`.
+pub fn traverse_expr_tree_with(expr: &Expr, config: &Config) -> Result<Value, Error> {
+    traverse_expr_tree_in(expr, config, &Environment::new())
+}
+/// Like `traverse_expr_tree_with`, looking variables up in `env`.
+pub fn traverse_expr_tree_in(
+    expr: &Expr,
+    config: &Config,
+    env: &Environment,
+) -> Result<Value, Error> {
+    Scope::new(config, env, &HostFunctions::new()).eval(expr)
+}
+/// Runs `statement` against `env`. An assignment stores the value and also
+/// returns it; a function definition returns `None`.
+pub fn execute(
+    statement: &Statement,
+    config: &Config,
+    env: &mut Environment,
+) -> Result<Option<Value>, Error> {
+    execute_in(statement, config, env, &HostFunctions::new())
+}
+pub(crate) fn execute_in(
+    statement: &Statement,
+    config: &Config,
+    env: &mut Environment,
+    functions: &HostFunctions,
+) -> Result<Option<Value>, Error> {
+    match statement {
+        Statement::Expr(expr) => Scope::new(config, env, functions).eval(expr).map(Some),
+        Statement::Assign { name, span, value } => {
+            if is_constant(name, env) {
+                return Err(Error::Eval(EvalError::ConstantAssignment, *span));
+            }
+            let value = Scope::new(config, env, functions).eval(value)?;
+            env.set(name, value.clone());
+            Ok(Some(value))
+        }
+        Statement::Define {
+            name, params, body, ..
+        } => {
+            let function = Function {
+                params: params.clone(),
+                body: body.clone(),
+            };
+            env.define(name, function);
+            Ok(None)
+        }
+    }
+}
+/// `execute_in` for the entry points that always produce a value, which
+/// reject a function definition with `ParseError::UnexpectedDefinition`.
+pub(crate) fn evaluate_statement(
+    statement: &Statement,
+    config: &Config,
+    env: &mut Environment,
+    functions: &HostFunctions,
+) -> Result<Value, Error> {
+    if let Statement::Define { span, .. } = statement {
+        return Err(Error::Parse(ParseError::UnexpectedDefinition, *span));
+    }
+    let value = execute_in(statement, config, env, functions)?;
+    Ok(value.expect("only definitions have no value"))
+}
+fn is_constant(name: &str, env: &Environment) -> bool {
+    env.constant(name).is_some() || builtin_constant(name).is_some()
+}
+enum Callee<'a> {
+    User(&'a Function),
+    Host(&'a HostFunction),
+    Builtin(&'static Builtin),
+}
+/// How deeply evaluation may nest, counting every parenthesis, operand and
+/// argument that encloses the expression being evaluated, including those
+/// in the bodies of the user-defined functions it is called from. A single
+/// line nests at most `MAX_NESTING` deep, so this only limits recursion
+/// through function bodies that `Config::max_call_depth` alone allows.
+pub const MAX_EVAL_DEPTH: usize = 500;
+/// What names in an expression resolve against.
+pub(crate) struct Scope<'a> {
+    config: &'a Config,
+    env: &'a Environment,
+    functions: &'a HostFunctions,
+    /// Arguments of the user-defined function being evaluated, which shadow
+    /// variables of the same name.
+    locals: &'a [(String, Value)],
+    /// How many user-defined function calls enclose this scope.
+    depth: usize,
+    /// How many calls to `eval` enclose the current one, counted across
+    /// function calls, against `MAX_EVAL_DEPTH`.
+    nesting: Cell<usize>,
+}
+impl<'a> Scope<'a> {
+    pub fn new(config: &'a Config, env: &'a Environment, functions: &'a HostFunctions) -> Self {
+        Scope {
+            config,
+            env,
+            functions,
+            locals: &[],
+            depth: 0,
+            nesting: Cell::new(0),
+        }
+    }
+    pub fn eval(&self, expr: &Expr) -> Result<Value, Error> {
+        let nesting = self.nesting.get();
+        if nesting == MAX_EVAL_DEPTH {
+            return Err(Error::Eval(EvalError::RecursionLimit, expr.span()));
+        }
+        self.nesting.set(nesting + 1);
+        let value = self.eval_node(expr);
+        self.nesting.set(nesting);
+        value
+    }
+    fn eval_node(&self, expr: &Expr) -> Result<Value, Error> {
+        let config = self.config;
+        match expr {
+            Expr::Number { value, span } => {
+                literal_value(value, config).map_err(|kind| Error::Eval(kind, *span))
+            }
+            Expr::Imaginary { value, span } => match config.mode {
+                Mode::Complex => Ok(Value::Complex(Complex::new(0.0, value.to_f64()))),
+                _ => Err(Error::Eval(EvalError::ImaginaryNumber, *span)),
+            },
+            Expr::Percent { .. } => self.percent(expr),
+            Expr::Bool { value, .. } => Ok(Value::Bool(*value)),
+            Expr::Variable { name, span } => {
+                self.variable(name).map_err(|kind| Error::Eval(kind, *span))
+            }
+            Expr::Call { .. } => self.call(expr),
+            Expr::Conditional { .. } => self.conditional(expr),
+            Expr::Group { inner, .. } => self.eval(inner),
+            Expr::Unary { .. } | Expr::Binary { .. } => self.chain(expr),
+        }
+    }
+    /// Applies the operators of a chain such as `1 + 2 + 3!` innermost first
+    /// in a loop, so a chain thousands of operators long evaluates without
+    /// recursing once per operator.
+    fn chain(&self, expr: &Expr) -> Result<Value, Error> {
+        let config = self.config;
+        let (links, first) = expr.chain();
+        let mut value = self.eval(first)?;
+        for link in links.into_iter().rev() {
+            value = match link {
+                Expr::Unary { op, span, .. } => {
+                    unary(*op, value, config).map_err(|kind| Error::Eval(kind, *span))?
+                }
+                Expr::Binary {
+                    op: op @ (Ops::And | Ops::Or),
+                    rhs,
+                    span,
+                    ..
+                } => self.logical(*op, value, rhs, *span)?,
+                Expr::Binary { op, rhs, span, .. } => {
+                    let right = self.right_operand(*op, &value, rhs)?;
+                    binary(*op, value, right, config).map_err(|kind| Error::Eval(kind, *span))?
+                }
+                _ => unreachable!("chains only link unary and binary nodes"),
+            };
+        }
+        Ok(value)
+    }
+    /// `&&` and `||`, which skip the right operand once the left one decides
+    /// the result, so `false && x` never evaluates `x`.
+    fn logical(&self, op: Ops, left: Value, rhs: &Expr, span: Span) -> Result<Value, Error> {
+        match (op, &left) {
+            (Ops::And, Value::Bool(false)) | (Ops::Or, Value::Bool(true)) => Ok(left),
+            (_, Value::Bool(_)) => {
+                let right = self.eval(rhs)?;
+                binary(op, left, right, self.config).map_err(|kind| Error::Eval(kind, span))
+            }
+            _ => Err(Error::Eval(EvalError::ExpectedBool, span)),
+        }
+    }
+    /// `b%` outside the calculator reading of `a + b%`, which is exactly
+    /// `b/100`.
+    fn percent(&self, expr: &Expr) -> Result<Value, Error> {
+        let Expr::Percent { value, span } = expr else {
+            unreachable!("only called for Expr::Percent")
+        };
+        let fraction = Literal {
+            digits: value.digits.clone(),
+            exponent: value.exponent - 2,
+        };
+        literal_value(&fraction, self.config).map_err(|kind| Error::Eval(kind, *span))
+    }
+    /// Under `Percent::Calculator` the `b%` of `a + b%` and `a - b%` is the
+    /// share `a * b / 100`, which the integer modes round toward zero like
+    /// `/` does. Any other right operand evaluates as usual.
+    fn right_operand(&self, op: Ops, left: &Value, rhs: &Expr) -> Result<Value, Error> {
+        let (Ops::Add | Ops::Subtract, Expr::Percent { value, span }, Percent::Calculator) =
+            (op, rhs, self.config.percent)
+        else {
+            return self.eval(rhs);
+        };
+        let config = self.config;
+        let hundred = Literal {
+            digits: BigInt::from(100),
+            exponent: 0,
+        };
+        let hundred = literal_value(&hundred, config).expect("100 fits every mode");
+        literal_value(value, config)
+            .and_then(|rate| binary(Ops::Multiply, left.clone(), rate, config))
+            .and_then(|product| binary(Ops::Divide, product, hundred, config))
+            .map_err(|kind| Error::Eval(kind, *span))
+    }
+    /// Evaluates only the branch the condition picks.
+    fn conditional(&self, expr: &Expr) -> Result<Value, Error> {
+        let Expr::Conditional {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } = expr
+        else {
+            unreachable!("only called for Expr::Conditional")
+        };
+        match self.eval(condition)? {
+            Value::Bool(true) => self.eval(then_branch),
+            Value::Bool(false) => self.eval(else_branch),
+            _ => Err(Error::Eval(EvalError::ExpectedBool, condition.span())),
+        }
+    }
+    /// Parameters shadow constants, which shadow variables.
+    fn variable(&self, name: &str) -> Result<Value, EvalError> {
+        if let Some((_, value)) = self.locals.iter().find(|(local, _)| local == name) {
+            return Ok(value.clone());
+        }
+        if let Some(value) = self.env.constant(name) {
+            return Ok(value.clone());
+        }
+        if let Some(literal) = builtin_constant(name) {
+            return match self.config.mode {
+                Mode::Integer | Mode::BigInt | Mode::Rational => Err(EvalError::NotExact),
+                _ => literal_value(&literal, self.config),
+            };
+        }
+        self.env
+            .get(name)
+            .cloned()
+            .ok_or(EvalError::UndefinedVariable)
+    }
+    // kept out of eval so its stack frame stays small for deeply nested
+    // expressions
+    fn call(&self, expr: &Expr) -> Result<Value, Error> {
+        let Expr::Call { name, args, span } = expr else {
+            unreachable!("only called for Expr::Call")
+        };
+        let span = *span;
+        let name_span = Span::new(span.start, span.start + name.len());
+        // definitions made in the expression language shadow host
+        // functions, which shadow built-ins
+        let callee = if let Some(function) = self.env.function(name) {
+            Callee::User(function)
+        } else if let Some(function) = self.functions.get(name.as_str()) {
+            Callee::Host(function)
+        } else {
+            builtin(name)
+                .map(Callee::Builtin)
+                .ok_or(Error::Eval(EvalError::UnknownFunction, name_span))?
+        };
+        let arity = match callee {
+            Callee::User(function) => Arity::exact(function.params.len()),
+            Callee::Host(function) => function.arity,
+            Callee::Builtin(function) => function.arity,
+        };
+        if !arity.accepts(args.len()) {
+            let kind = EvalError::WrongArity {
+                expected: arity,
+                found: args.len(),
+            };
+            return Err(Error::Eval(kind, span));
+        }
+        let args = args
+            .iter()
+            .map(|arg| self.eval(arg))
+            .collect::<Result<Vec<_>, _>>()?;
+        let result = match callee {
+            Callee::User(function) => return self.call_user(function, args, span),
+            Callee::Host(function) => function.call(&args),
+            // built-ins only take numbers
+            Callee::Builtin(_) if args.iter().any(|arg| matches!(arg, Value::Bool(_))) => {
+                Err(EvalError::ExpectedNumber)
+            }
+            Callee::Builtin(function) => (function.call)(&args, self.config),
+        };
+        result.map_err(|kind| Error::Eval(kind, span))
+    }
+    /// Evaluates the body of `function` with its parameters bound to `args`.
+    /// Errors inside the body point at the call, since the body's spans refer
+    /// to the line that defined it.
+    fn call_user(&self, function: &Function, args: Vec<Value>, span: Span) -> Result<Value, Error> {
+        if self.depth >= self.config.max_call_depth {
+            return Err(Error::Eval(EvalError::RecursionLimit, span));
+        }
+        let locals: Vec<(String, Value)> = function.params.iter().cloned().zip(args).collect();
+        let scope = Scope {
+            config: self.config,
+            env: self.env,
+            functions: self.functions,
+            locals: &locals,
+            depth: self.depth + 1,
+            nesting: self.nesting.clone(),
+        };
+        scope.eval(&function.body).map_err(|error| match error {
+            Error::Eval(kind, _) => Error::Eval(kind, span),
+            error => error,
+        })
+    }
+}
+fn literal_value(literal: &Literal, config: &Config) -> Result<Value, EvalError> {
+    match config.mode {
+        Mode::Integer => literal
+            .to_integer()?
+            .to_i64()
+            .map(Value::Int)
+            .ok_or(EvalError::Overflow),
+        Mode::BigInt => literal.to_integer().map(Value::Big),
+        Mode::Float => Ok(Value::Float(literal.to_f64())),
+        Mode::Rational => literal.to_rational().map(Value::Rational),
+        Mode::Complex => Ok(Value::Complex(Complex::new(literal.to_f64(), 0.0))),
+        Mode::Decimal => round_decimal(&literal.to_rational()?, &config.decimal),
+    }
+}
+pub(crate) fn unary(op: UnaryOp, value: Value, config: &Config) -> Result<Value, EvalError> {
+    match op {
+        UnaryOp::Not => match value {
+            Value::Bool(value) => Ok(Value::Bool(!value)),
+            _ => Err(EvalError::ExpectedBool),
+        },
+        UnaryOp::Plus => match value {
+            Value::Bool(_) => Err(EvalError::ExpectedNumber),
+            value => Ok(value),
+        },
+        UnaryOp::Minus => match value {
+            Value::Int(value) => neg(value, config.overflow)
+                .map(Value::Int)
+                .ok_or(EvalError::Overflow),
+            Value::Big(value) => Ok(Value::Big(-&value)),
+            Value::Float(value) => Ok(Value::Float(-value)),
+            Value::Rational(value) => Ok(Value::Rational(value.neg())),
+            Value::Complex(value) => Ok(Value::Complex(value.neg())),
+            Value::Decimal(value) => Ok(Value::Decimal(Decimal::new(
+                -value.unscaled(),
+                value.scale(),
+            ))),
+            Value::Bool(_) => Err(EvalError::ExpectedNumber),
+        },
+        UnaryOp::BitNot => match value {
+            Value::Int(value) => Ok(Value::Int(!value)),
+            value => {
+                let flipped = &-&exact_integer(&value)? - &BigInt::from(1);
+                integer_like(flipped, &value, config)
+            }
+        },
+        UnaryOp::Sqrt => sqrt(std::slice::from_ref(&value), config),
+        UnaryOp::Factorial => factorial(&value, 1, config),
+        UnaryOp::DoubleFactorial => factorial(&value, 2, config),
+    }
+}
+/// Arguments above this overflow in every integer mode: even `n!!` has more
+/// than `MAX_DIGITS` digits by then.
+const MAX_FACTORIAL: i64 = 100_000;
+
+/// `n!` for a `step` of 1 and `n!!` for a `step` of 2. Floats follow IEEE
+/// 754 and become infinite instead of overflowing.
+fn factorial(value: &Value, step: i64, config: &Config) -> Result<Value, EvalError> {
+    match value {
+        Value::Int(value) => {
+            let mut acc: i64 = 1;
+            let mut term = factorial_argument(&BigInt::from(*value))?;
+            // a wrapped product stays zero once it gets there
+            while term > 1 && acc != 0 {
+                acc = mul(acc, term, config.overflow).ok_or(EvalError::Overflow)?;
+                term -= step;
+            }
+            Ok(Value::Int(acc))
+        }
+        Value::Big(value) => big_factorial(value, step).map(Value::Big),
+        Value::Float(value) => float_factorial(*value, step).map(Value::Float),
+        Value::Complex(value) if value.im != 0.0 => Err(EvalError::NotReal),
+        Value::Complex(value) => {
+            float_factorial(value.re, step).map(|re| Value::Complex(Complex::new(re, 0.0)))
+        }
+        Value::Bool(_) => Err(EvalError::ExpectedNumber),
+        Value::Rational(_) | Value::Decimal(_) => {
+            let exact = value.to_rational();
+            if !exact.is_integer() {
+                return Err(EvalError::NotAnInteger);
+            }
+            let result = Rational::from(big_factorial(exact.numer(), step)?);
+            match value {
+                Value::Decimal(_) => round_decimal(&result, &config.decimal),
+                _ => Ok(Value::Rational(result)),
+            }
+        }
+    }
+}
+fn factorial_argument(value: &BigInt) -> Result<i64, EvalError> {
+    if value.is_negative() {
+        return Err(EvalError::NegativeFactorial);
+    }
+    match value.to_i64() {
+        Some(value) if value <= MAX_FACTORIAL => Ok(value),
+        _ => Err(EvalError::Overflow),
+    }
+}
+fn big_factorial(value: &BigInt, step: i64) -> Result<BigInt, EvalError> {
+    let mut acc = BigInt::from(1);
+    let mut term = factorial_argument(value)?;
+    while term > 1 {
+        acc = &acc * &BigInt::from(term);
+        if acc.log10_abs() > MAX_DIGITS {
+            return Err(EvalError::Overflow);
+        }
+        term -= step;
+    }
+    Ok(acc)
+}
+fn float_factorial(value: f64, step: i64) -> Result<f64, EvalError> {
+    if value < 0.0 {
+        return Err(EvalError::NegativeFactorial);
+    }
+    if value.fract() != 0.0 && value.is_finite() {
+        return Err(EvalError::NotAnInteger);
+    }
+    let mut acc: f64 = 1.0;
+    let mut term = value;
+    // also stops for NaN, which compares false
+    while term > 1.0 && acc.is_finite() {
+        acc *= term;
+        term -= step as f64;
+    }
+    Ok(if value.is_nan() { value } else { acc })
+}
+fn binary(op: Ops, left: Value, right: Value, config: &Config) -> Result<Value, EvalError> {
+    match op {
+        Ops::Equal
+        | Ops::NotEqual
+        | Ops::Less
+        | Ops::LessEqual
+        | Ops::Greater
+        | Ops::GreaterEqual => return comparison(op, &left, &right).map(Value::Bool),
+        Ops::And | Ops::Or => {
+            return match (left, right) {
+                (Value::Bool(left), Value::Bool(right)) if op == Ops::And => {
+                    Ok(Value::Bool(left && right))
+                }
+                (Value::Bool(left), Value::Bool(right)) => Ok(Value::Bool(left || right)),
+                _ => Err(EvalError::ExpectedBool),
+            }
+        }
+        _ => {}
+    }
+    match (left, right) {
+        (Value::Bool(_), _) | (_, Value::Bool(_)) => Err(EvalError::ExpectedNumber),
+        (Value::Int(left), Value::Int(right)) => {
+            int_binary(op, left, right, config.overflow).map(Value::Int)
+        }
+        (left, right) if is_bitwise(op) => bitwise_binary(op, &left, &right, config),
+        (left @ Value::Complex(_), right) | (left, right @ Value::Complex(_)) => {
+            complex_binary(op, &left.to_complex(), &right.to_complex()).map(Value::Complex)
+        }
+        (left @ Value::Float(_), right) | (left, right @ Value::Float(_)) => Ok(Value::Float(
+            float_binary(op, left.to_f64(), right.to_f64()),
+        )),
+        (left @ Value::Rational(_), right) | (left, right @ Value::Rational(_)) => {
+            rational_binary(op, &left.to_rational(), &right.to_rational()).map(Value::Rational)
+        }
+        (left @ Value::Decimal(_), right) | (left, right @ Value::Decimal(_)) => {
+            let exact = rational_binary(op, &left.to_rational(), &right.to_rational())?;
+            round_decimal(&exact, &config.decimal)
+        }
+        (left, right) => big_binary(op, &left.to_big(), &right.to_big()).map(Value::Big),
+    }
+}
+fn is_bitwise(op: Ops) -> bool {
+    matches!(
+        op,
+        Ops::BitAnd | Ops::BitOr | Ops::BitXor | Ops::ShiftLeft | Ops::ShiftRight
+    )
+}
+/// Bitwise operators on anything but two `Value::Int`s. Both operands must
+/// be whole numbers, and the result goes back to the type `binary` would
+/// have promoted them to, so `0xF0 & 0x3C` in float mode is `48` as a float.
+fn bitwise_binary(
+    op: Ops,
+    left: &Value,
+    right: &Value,
+    config: &Config,
+) -> Result<Value, EvalError> {
+    let result = big_binary(op, &exact_integer(left)?, &exact_integer(right)?)?;
+    let like = match (left, right) {
+        (like @ Value::Complex(_), _) | (_, like @ Value::Complex(_)) => like,
+        (like @ Value::Float(_), _) | (_, like @ Value::Float(_)) => like,
+        (like @ Value::Rational(_), _) | (_, like @ Value::Rational(_)) => like,
+        (like @ Value::Decimal(_), _) | (_, like @ Value::Decimal(_)) => like,
+        (like, _) => like,
+    };
+    integer_like(result, like, config)
+}
+/// The value as an integer, or `EvalError::NotAnInteger` if it has a
+/// fractional part.
+fn exact_integer(value: &Value) -> Result<BigInt, EvalError> {
+    match value {
+        Value::Int(value) => Ok(BigInt::from(*value)),
+        Value::Big(value) => Ok(value.clone()),
+        Value::Complex(value) if value.im != 0.0 => Err(EvalError::NotReal),
+        Value::Float(_) | Value::Complex(_) => Literal::from_f64(value.to_complex().re)
+            .ok_or(EvalError::NotAnInteger)?
+            .to_integer(),
+        Value::Rational(_) | Value::Decimal(_) => {
+            let exact = value.to_rational();
+            if !exact.is_integer() {
+                return Err(EvalError::NotAnInteger);
+            }
+            Ok(exact.numer().clone())
+        }
+        Value::Bool(_) => Err(EvalError::ExpectedNumber),
+    }
+}
+/// `value` as the same kind of number as `like`, with `Value::Int` widened
+/// to `Value::Big`.
+fn integer_like(value: BigInt, like: &Value, config: &Config) -> Result<Value, EvalError> {
+    match like {
+        Value::Int(_) | Value::Big(_) => Ok(Value::Big(value)),
+        Value::Float(_) => Ok(Value::Float(value.to_f64())),
+        Value::Complex(_) => Ok(Value::Complex(Complex::new(value.to_f64(), 0.0))),
+        Value::Rational(_) => Ok(Value::Rational(Rational::from(value))),
+        Value::Decimal(_) => round_decimal(&Rational::from(value), &config.decimal),
+        Value::Bool(_) => Err(EvalError::ExpectedNumber),
+    }
+}
+/// Booleans can only be compared for equality, with each other. NaN is
+/// unequal to everything, itself included.
+fn comparison(op: Ops, left: &Value, right: &Value) -> Result<bool, EvalError> {
+    if let Ops::Equal | Ops::NotEqual = op {
+        let equal = match (left, right) {
+            (Value::Bool(left), Value::Bool(right)) => left == right,
+            (Value::Bool(_), _) | (_, Value::Bool(_)) => return Err(EvalError::ExpectedNumber),
+            // complex numbers off the real axis are equal or not, but unordered
+            (Value::Complex(_), _) | (_, Value::Complex(_)) => {
+                left.to_complex() == right.to_complex()
+            }
+            _ => compare(left, right)? == Some(Ordering::Equal),
+        };
+        return Ok(equal == (op == Ops::Equal));
+    }
+    let Some(ordering) = compare(left, right)? else {
+        return Ok(false);
+    };
+    Ok(match op {
+        Ops::Less => ordering == Ordering::Less,
+        Ops::LessEqual => ordering != Ordering::Greater,
+        Ops::Greater => ordering == Ordering::Greater,
+        Ops::GreaterEqual => ordering != Ordering::Less,
+        _ => unreachable!("{} is not a comparison", op),
+    })
+}
+/// Orders two values after the same promotion as `binary`. `None` means a
+/// NaN was involved; complex numbers off the real axis cannot be ordered.
+pub(crate) fn compare(left: &Value, right: &Value) -> Result<Option<Ordering>, EvalError> {
+    Ok(match (left, right) {
+        (Value::Bool(_), _) | (_, Value::Bool(_)) => return Err(EvalError::ExpectedNumber),
+        (Value::Int(left), Value::Int(right)) => Some(left.cmp(right)),
+        (left @ Value::Complex(_), right) | (left, right @ Value::Complex(_)) => {
+            let (left, right) = (left.to_complex(), right.to_complex());
+            if left.im != 0.0 || right.im != 0.0 {
+                return Err(EvalError::NotReal);
+            }
+            left.re.partial_cmp(&right.re)
+        }
+        (left @ Value::Float(_), right) | (left, right @ Value::Float(_)) => {
+            left.to_f64().partial_cmp(&right.to_f64())
+        }
+        (left @ (Value::Rational(_) | Value::Decimal(_)), right)
+        | (left, right @ (Value::Rational(_) | Value::Decimal(_))) => {
+            Some(left.to_rational().cmp(&right.to_rational()))
+        }
+        (left, right) => Some(left.to_big().cmp(&right.to_big())),
+    })
+}
+fn int_binary(op: Ops, left: i64, right: i64, mode: Overflow) -> Result<i64, EvalError> {
+    let result = match op {
+        Ops::Add => add(left, right, mode),
+        Ops::Subtract => sub(left, right, mode),
+        Ops::Multiply => mul(left, right, mode),
+        Ops::Divide => {
+            if right == 0 {
+                return Err(EvalError::DivideByZero);
+            }
+            div(left, right, mode)
+        }
+        Ops::Exponent => {
+            if right < 0 {
+                return Err(EvalError::NegativeExponent);
+            }
+            pow(left, right, mode)
+        }
+        Ops::Equal
+        | Ops::NotEqual
+        | Ops::Less
+        | Ops::LessEqual
+        | Ops::Greater
+        | Ops::GreaterEqual
+        | Ops::And
+        | Ops::Or => unreachable!("binary handles {} without promoting", op),
+        Ops::BitAnd => Some(left & right),
+        Ops::BitOr => Some(left | right),
+        Ops::BitXor => Some(left ^ right),
+        Ops::ShiftLeft | Ops::ShiftRight if right < 0 => return Err(EvalError::NegativeShift),
+        Ops::ShiftLeft => shl(left, right, mode),
+        // shifting out every bit leaves only the sign
+        Ops::ShiftRight => Some(left >> right.min(63)),
+        Ops::Remainder | Ops::Modulo | Ops::FloorDivide if right == 0 => {
+            return Err(EvalError::DivideByZero)
+        }
+        // the remainder always fits, even for i64::MIN % -1
+        Ops::Remainder => Some(left.wrapping_rem(right)),
+        Ops::Modulo => Some(left.wrapping_rem_euclid(right)),
+        Ops::FloorDivide => {
+            let remainder = left.wrapping_rem(right);
+            // truncating division rounded a negative quotient up
+            let adjust = i64::from(remainder != 0 && (remainder < 0) != (right < 0));
+            div(left, right, mode).map(|quotient| quotient - adjust)
+        }
+    };
+    result.ok_or(EvalError::Overflow)
+}
+fn big_binary(op: Ops, left: &BigInt, right: &BigInt) -> Result<BigInt, EvalError> {
+    match op {
+        Ops::Add => Ok(left + right),
+        Ops::Subtract => Ok(left - right),
+        Ops::Multiply => Ok(left * right),
+        Ops::Divide => left
+            .div_rem(right)
+            .map(|(quotient, _)| quotient)
+            .ok_or(EvalError::DivideByZero),
+        Ops::Exponent => big_pow(left, right),
+        Ops::Equal
+        | Ops::NotEqual
+        | Ops::Less
+        | Ops::LessEqual
+        | Ops::Greater
+        | Ops::GreaterEqual
+        | Ops::And
+        | Ops::Or => unreachable!("binary handles {} without promoting", op),
+        Ops::BitAnd => Ok(left.bitand(right)),
+        Ops::BitOr => Ok(left.bitor(right)),
+        Ops::BitXor => Ok(left.bitxor(right)),
+        Ops::ShiftLeft | Ops::ShiftRight if right.is_negative() => Err(EvalError::NegativeShift),
+        Ops::ShiftLeft if left.is_zero() => Ok(BigInt::zero()),
+        Ops::ShiftLeft => Ok(left * &big_pow(&BigInt::from(2), right)?),
+        Ops::ShiftRight => match big_pow(&BigInt::from(2), right) {
+            Ok(power) => big_binary(Ops::FloorDivide, left, &power),
+            // a power of two with more digits than allowed exceeds `left`
+            Err(_) if left.is_negative() => Ok(BigInt::from(-1)),
+            Err(_) => Ok(BigInt::zero()),
+        },
+        Ops::Remainder | Ops::Modulo | Ops::FloorDivide => {
+            let (quotient, remainder) = left.div_rem(right).ok_or(EvalError::DivideByZero)?;
+            Ok(match op {
+                Ops::Modulo if remainder.is_negative() => &remainder + &right.abs(),
+                Ops::FloorDivide
+                    if !remainder.is_zero() && remainder.is_negative() != right.is_negative() =>
+                {
+                    &quotient - &BigInt::from(1)
+                }
+                Ops::FloorDivide => quotient,
+                _ => remainder,
+            })
+        }
+    }
+}
+fn rational_binary(op: Ops, left: &Rational, right: &Rational) -> Result<Rational, EvalError> {
+    match op {
+        Ops::Add => Ok(left.add(right)),
+        Ops::Subtract => Ok(left.sub(right)),
+        Ops::Multiply => Ok(left.mul(right)),
+        Ops::Divide => left.div(right).ok_or(EvalError::DivideByZero),
+        Ops::Exponent => left.pow(right),
+        Ops::BitAnd
+        | Ops::BitOr
+        | Ops::BitXor
+        | Ops::ShiftLeft
+        | Ops::ShiftRight
+        | Ops::Equal
+        | Ops::NotEqual
+        | Ops::Less
+        | Ops::LessEqual
+        | Ops::Greater
+        | Ops::GreaterEqual
+        | Ops::And
+        | Ops::Or => unreachable!("binary handles {} without promoting", op),
+        Ops::Remainder | Ops::Modulo | Ops::FloorDivide => {
+            let quotient = left.div(right).ok_or(EvalError::DivideByZero)?;
+            if op == Ops::FloorDivide {
+                return Ok(Rational::from(quotient.floor()));
+            }
+            let remainder = left.sub(&right.mul(&Rational::from(quotient.trunc())));
+            if op == Ops::Modulo && remainder.numer().is_negative() {
+                let magnitude = if right.numer().is_negative() {
+                    right.neg()
+                } else {
+                    right.clone()
+                };
+                return Ok(remainder.add(&magnitude));
+            }
+            Ok(remainder)
+        }
+    }
+}
+fn round_decimal(value: &Rational, config: &DecimalConfig) -> Result<Value, EvalError> {
+    Decimal::from_rational(value, config.scale, config.rounding).map(Value::Decimal)
+}
+fn complex_binary(op: Ops, left: &Complex, right: &Complex) -> Result<Complex, EvalError> {
+    match op {
+        Ops::Add => Ok(left.add(right)),
+        Ops::Subtract => Ok(left.sub(right)),
+        Ops::Multiply => Ok(left.mul(right)),
+        Ops::Divide => left.div(right).ok_or(EvalError::DivideByZero),
+        Ops::Exponent => left.pow(right).ok_or(EvalError::DivideByZero),
+        Ops::BitAnd
+        | Ops::BitOr
+        | Ops::BitXor
+        | Ops::ShiftLeft
+        | Ops::ShiftRight
+        | Ops::Equal
+        | Ops::NotEqual
+        | Ops::Less
+        | Ops::LessEqual
+        | Ops::Greater
+        | Ops::GreaterEqual
+        | Ops::And
+        | Ops::Or => unreachable!("binary handles {} without promoting", op),
+        // only defined on the real axis, where they work like the float
+        // versions apart from rejecting a zero divisor
+        Ops::Remainder | Ops::Modulo | Ops::FloorDivide => {
+            if left.im != 0.0 || right.im != 0.0 {
+                return Err(EvalError::NotReal);
+            }
+            if right.re == 0.0 {
+                return Err(EvalError::DivideByZero);
+            }
+            Ok(Complex::new(float_binary(op, left.re, right.re), 0.0))
+        }
+    }
+}
+/// Plain IEEE 754 arithmetic: dividing by zero gives an infinity or NaN.
+fn float_binary(op: Ops, left: f64, right: f64) -> f64 {
+    match op {
+        Ops::Add => left + right,
+        Ops::Subtract => left - right,
+        Ops::Multiply => left * right,
+        Ops::Divide => left / right,
+        Ops::Exponent => left.powf(right),
+        Ops::Remainder => left % right,
+        Ops::Modulo => left.rem_euclid(right),
+        Ops::FloorDivide => (left / right).floor(),
+        Ops::BitAnd
+        | Ops::BitOr
+        | Ops::BitXor
+        | Ops::ShiftLeft
+        | Ops::ShiftRight
+        | Ops::Equal
+        | Ops::NotEqual
+        | Ops::Less
+        | Ops::LessEqual
+        | Ops::Greater
+        | Ops::GreaterEqual
+        | Ops::And
+        | Ops::Or => unreachable!("binary handles {} without promoting", op),
+    }
+}
+fn big_pow(base: &BigInt, exp: &BigInt) -> Result<BigInt, EvalError> {
+    if exp.is_negative() {
+        return Err(EvalError::NegativeExponent);
+    }
+    let one = BigInt::from(1);
+    if base.is_zero() || *base == one {
+        return Ok(if exp.is_zero() { one } else { base.clone() });
+    }
+    if *base == -&one {
+        let (_, parity) = exp.div_rem(&BigInt::from(2)).expect("nonzero divisor");
+        return Ok(if parity.is_zero() { one } else { base.clone() });
+    }
+    match exp.to_i64().and_then(|exp| u32::try_from(exp).ok()) {
+        Some(exp) if base.log10_abs() * exp as f64 <= MAX_DIGITS => Ok(base.pow(exp)),
+        _ => Err(EvalError::Overflow),
+    }
+}
+// Each helper returns None only when `mode` is `Overflow::Checked` and the
+// result does not fit in an i64.
+fn neg(value: i64, mode: Overflow) -> Option<i64> {
+    match mode {
+        Overflow::Checked => value.checked_neg(),
+        Overflow::Wrapping => Some(value.wrapping_neg()),
+        Overflow::Saturating => Some(value.saturating_neg()),
+    }
+}
+fn add(left: i64, right: i64, mode: Overflow) -> Option<i64> {
+    match mode {
+        Overflow::Checked => left.checked_add(right),
+        Overflow::Wrapping => Some(left.wrapping_add(right)),
+        Overflow::Saturating => Some(left.saturating_add(right)),
+    }
+}
+fn sub(left: i64, right: i64, mode: Overflow) -> Option<i64> {
+    match mode {
+        Overflow::Checked => left.checked_sub(right),
+        Overflow::Wrapping => Some(left.wrapping_sub(right)),
+        Overflow::Saturating => Some(left.saturating_sub(right)),
+    }
+}
+fn mul(left: i64, right: i64, mode: Overflow) -> Option<i64> {
+    match mode {
+        Overflow::Checked => left.checked_mul(right),
+        Overflow::Wrapping => Some(left.wrapping_mul(right)),
+        Overflow::Saturating => Some(left.saturating_mul(right)),
+    }
+}
+fn div(left: i64, right: i64, mode: Overflow) -> Option<i64> {
+    match mode {
+        Overflow::Checked => left.checked_div(right),
+        Overflow::Wrapping => Some(left.wrapping_div(right)),
+        Overflow::Saturating => Some(left.saturating_div(right)),
+    }
+}
+/// `value * 2^count` for a nonnegative `count`, overflowing like `mul`.
+fn shl(value: i64, count: i64, mode: Overflow) -> Option<i64> {
+    if count < 63 {
+        return mul(value, 1 << count, mode);
+    }
+    match mode {
+        Overflow::Wrapping if count < 64 => Some(value.wrapping_shl(count as u32)),
+        Overflow::Wrapping => Some(0),
+        _ if value == 0 => Some(0),
+        Overflow::Checked => (count == 63 && value == -1).then_some(i64::MIN),
+        Overflow::Saturating if value < 0 => Some(i64::MIN),
+        Overflow::Saturating => Some(i64::MAX),
+    }
+}
+/// Exponentiation by squaring so exponents beyond `u32::MAX` still work for
+/// bases like 0, 1 and -1.
+fn pow(mut base: i64, mut exp: i64, mode: Overflow) -> Option<i64> {
+    let mut acc: i64 = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mul(acc, base, mode)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = mul(base, base, mode)?;
+        }
+    }
+    Some(acc)
+}
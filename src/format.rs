@@ -0,0 +1,121 @@
+use crate::bigint::BigInt;
+use crate::complex::Complex;
+use crate::value::Value;
+
+/// Decimal places used for `RationalStyle::Decimal` when no precision is set.
+const DEFAULT_RATIONAL_PLACES: usize = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RationalStyle {
+    /// `7/2`
+    #[default]
+    Fraction,
+    /// `3 1/2`
+    Mixed,
+    /// `3.5`, rounded to the format's precision.
+    Decimal,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ComplexStyle {
+    /// `1+2i`
+    #[default]
+    Rectangular,
+    /// `2.23606797749979*e^(1.1071487177940904i)`, the modulus times `e` to
+    /// the argument.
+    Polar,
+}
+/// Base used to print integers; other numbers are always printed in
+/// decimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Radix {
+    #[default]
+    Decimal,
+    /// `0xFF`
+    Hex,
+    /// `0b1010`
+    Binary,
+    /// `0o17`
+    Octal,
+}
+/// How `format_value` renders a `Value`.
+#[derive(Debug, Clone, Default)]
+pub struct Format {
+    /// Round floats and decimal rationals to this many decimal places;
+    /// trailing zeroes are dropped. `None` prints floats as the shortest text
+    /// that reads back as the same float.
+    pub precision: Option<usize>,
+    pub rational: RationalStyle,
+    pub complex: ComplexStyle,
+    pub radix: Radix,
+}
+pub fn format_value(value: &Value, format: &Format) -> String {
+    match value {
+        Value::Float(value) => format_float(*value, format.precision),
+        Value::Rational(value) => match format.rational {
+            RationalStyle::Fraction => value.to_string(),
+            RationalStyle::Mixed => value.to_mixed_string(),
+            RationalStyle::Decimal => {
+                value.to_decimal_string(format.precision.unwrap_or(DEFAULT_RATIONAL_PLACES))
+            }
+        },
+        Value::Complex(value) => format_complex(value, format),
+        Value::Int(value) => format_integer(&BigInt::from(*value), format.radix),
+        Value::Big(value) => format_integer(value, format.radix),
+        _ => value.to_string(),
+    }
+}
+/// Negative numbers print as `-0x10`, not in two's complement.
+fn format_integer(value: &BigInt, radix: Radix) -> String {
+    let (prefix, radix) = match radix {
+        Radix::Decimal => return value.to_string(),
+        Radix::Hex => ("0x", 16),
+        Radix::Binary => ("0b", 2),
+        Radix::Octal => ("0o", 8),
+    };
+    let digits = value.to_string_radix(radix);
+    match digits.strip_prefix('-') {
+        Some(digits) => format!("-{}{}", prefix, digits),
+        None => format!("{}{}", prefix, digits),
+    }
+}
+pub(crate) fn format_complex(value: &Complex, format: &Format) -> String {
+    let float = |part: f64| format_float(part, format.precision);
+    match format.complex {
+        ComplexStyle::Rectangular => {
+            let im = match float(value.im).as_str() {
+                "0" => return float(value.re),
+                "1" => "i".to_string(),
+                "-1" => "-i".to_string(),
+                im => format!("{}i", im),
+            };
+            match float(value.re).as_str() {
+                "0" => im,
+                re if im.starts_with('-') => format!("{}{}", re, im),
+                re => format!("{}+{}", re, im),
+            }
+        }
+        ComplexStyle::Polar => match float(value.arg()).as_str() {
+            "0" => float(value.abs()),
+            arg => format!("{}*e^({}i)", float(value.abs()), arg),
+        },
+    }
+}
+fn format_float(value: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(places) if value.is_finite() => {
+            let fixed = format!("{:.*}", places, value);
+            let trimmed = if fixed.contains('.') {
+                fixed.trim_end_matches('0').trim_end_matches('.')
+            } else {
+                &fixed
+            };
+            // rounding can leave "-0" behind
+            if trimmed == "-0" {
+                "0".to_string()
+            } else {
+                trimmed.to_string()
+            }
+        }
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,309 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::ast::UnaryOp;
+use crate::bigint::BigInt;
+use crate::complex::Complex;
+use crate::config::Config;
+use crate::decimal::Decimal;
+use crate::error::EvalError;
+use crate::eval::{compare, unary};
+use crate::literal::Literal;
+use crate::rational::Rational;
+use crate::value::Value;
+
+/// How many arguments a function accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arity {
+    pub min: usize,
+    /// `None` for functions such as `max` that take any number of arguments.
+    pub max: Option<usize>,
+}
+impl Arity {
+    pub const fn exact(count: usize) -> Arity {
+        Arity {
+            min: count,
+            max: Some(count),
+        }
+    }
+    pub const fn at_least(count: usize) -> Arity {
+        Arity {
+            min: count,
+            max: None,
+        }
+    }
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{} argument{}", max, plural(max)),
+            Some(max) => write!(f, "{} to {} arguments", self.min, max),
+            None => write!(f, "at least {} argument{}", self.min, plural(self.min)),
+        }
+    }
+}
+/// A function built into the evaluator, callable as `name(args)`.
+pub(crate) struct Builtin {
+    pub name: &'static str,
+    pub arity: Arity,
+    pub call: fn(&[Value], &Config) -> Result<Value, EvalError>,
+}
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "abs",
+        arity: Arity::exact(1),
+        call: abs,
+    },
+    Builtin {
+        name: "min",
+        arity: Arity::at_least(1),
+        call: min,
+    },
+    Builtin {
+        name: "max",
+        arity: Arity::at_least(1),
+        call: max,
+    },
+    Builtin {
+        name: "sqrt",
+        arity: Arity::exact(1),
+        call: sqrt,
+    },
+    Builtin {
+        name: "floor",
+        arity: Arity::exact(1),
+        call: floor,
+    },
+    Builtin {
+        name: "ceil",
+        arity: Arity::exact(1),
+        call: ceil,
+    },
+    Builtin {
+        name: "round",
+        arity: Arity::exact(1),
+        call: round,
+    },
+    Builtin {
+        name: "exp",
+        arity: Arity::exact(1),
+        call: exp,
+    },
+    Builtin {
+        name: "ln",
+        arity: Arity::exact(1),
+        call: ln,
+    },
+    Builtin {
+        name: "log",
+        arity: Arity {
+            min: 1,
+            max: Some(2),
+        },
+        call: log,
+    },
+    Builtin {
+        name: "sin",
+        arity: Arity::exact(1),
+        call: sin,
+    },
+    Builtin {
+        name: "cos",
+        arity: Arity::exact(1),
+        call: cos,
+    },
+    Builtin {
+        name: "tan",
+        arity: Arity::exact(1),
+        call: tan,
+    },
+    Builtin {
+        name: "asin",
+        arity: Arity::exact(1),
+        call: asin,
+    },
+    Builtin {
+        name: "acos",
+        arity: Arity::exact(1),
+        call: acos,
+    },
+    Builtin {
+        name: "atan",
+        arity: Arity::exact(1),
+        call: atan,
+    },
+];
+pub(crate) fn builtin(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name == name)
+}
+/// Names of the built-in functions, in the order they are listed in the
+/// README.
+pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+    BUILTINS.iter().map(|builtin| builtin.name)
+}
+
+fn abs(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    match &args[0] {
+        Value::Complex(value) => Ok(Value::Complex(Complex::new(value.abs(), 0.0))),
+        value if compare(value, &Value::Int(0))? == Some(Ordering::Less) => {
+            unary(UnaryOp::Minus, value.clone(), config)
+        }
+        value => Ok(value.clone()),
+    }
+}
+fn min(args: &[Value], _: &Config) -> Result<Value, EvalError> {
+    extreme(args, Ordering::Less)
+}
+fn max(args: &[Value], _: &Config) -> Result<Value, EvalError> {
+    extreme(args, Ordering::Greater)
+}
+/// The argument that compares as `wanted` against all others; a NaN
+/// argument makes the result NaN.
+fn extreme(args: &[Value], wanted: Ordering) -> Result<Value, EvalError> {
+    let mut best = &args[0];
+    for arg in &args[1..] {
+        match compare(arg, best)? {
+            Some(ordering) if ordering == wanted => best = arg,
+            Some(_) => {}
+            None => return Ok(Value::Float(f64::NAN)),
+        }
+    }
+    Ok(best.clone())
+}
+/// Exact in the integer and rational modes, where a root that is not a
+/// whole number (or ratio of them) is `EvalError::NotExact`.
+pub(crate) fn sqrt(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    let exact_root = |value: &BigInt| {
+        let root = value.isqrt().ok_or(EvalError::Domain)?;
+        if &root * &root == *value {
+            Ok(root)
+        } else {
+            Err(EvalError::NotExact)
+        }
+    };
+    match &args[0] {
+        Value::Int(value) => {
+            let root = exact_root(&BigInt::from(*value))?;
+            Ok(Value::Int(root.to_i64().expect("root of an i64 fits")))
+        }
+        Value::Big(value) => exact_root(value).map(Value::Big),
+        Value::Rational(value) => {
+            let root = Rational::new(exact_root(value.numer())?, exact_root(value.denom())?);
+            Ok(Value::Rational(root.expect("nonzero denominator")))
+        }
+        value => transcendental(value, config, f64::sqrt, |value| Ok(value.sqrt())),
+    }
+}
+fn floor(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    to_integer(&args[0], config, f64::floor, Rational::floor)
+}
+fn ceil(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    to_integer(&args[0], config, f64::ceil, Rational::ceil)
+}
+/// Rounds halves away from zero, like `f64::round`.
+fn round(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    to_integer(&args[0], config, f64::round, Rational::round)
+}
+fn to_integer(
+    value: &Value,
+    config: &Config,
+    real: fn(f64) -> f64,
+    exact: fn(&Rational) -> BigInt,
+) -> Result<Value, EvalError> {
+    match value {
+        Value::Int(_) | Value::Big(_) => Ok(value.clone()),
+        Value::Rational(value) => Ok(Value::Rational(Rational::from(exact(value)))),
+        Value::Decimal(value) => {
+            let rounded = Rational::from(exact(&value.to_rational()));
+            Decimal::from_rational(&rounded, value.scale(), config.decimal.rounding)
+                .map(Value::Decimal)
+        }
+        value => transcendental(value, config, real, |_| Err(EvalError::NotReal)),
+    }
+}
+fn exp(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    transcendental(&args[0], config, f64::exp, |value| Ok(value.exp()))
+}
+fn ln(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    transcendental(&args[0], config, f64::ln, |value| Ok(value.ln()))
+}
+/// `log(x)` is the base-10 logarithm, `log(x, b)` the base-`b` one.
+fn log(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    let Some(base) = args.get(1) else {
+        return transcendental(&args[0], config, f64::log10, |value| {
+            Ok(value
+                .ln()
+                .div(&Complex::new(10f64.ln(), 0.0))
+                .expect("nonzero"))
+        });
+    };
+    if let (Value::Complex(_), _) | (_, Value::Complex(_)) = (&args[0], base) {
+        let (value, base) = (args[0].to_complex(), base.to_complex());
+        return value
+            .ln()
+            .div(&base.ln())
+            .map(Value::Complex)
+            .ok_or(EvalError::Domain);
+    }
+    let base = base.to_f64();
+    transcendental(
+        &args[0],
+        config,
+        |value| value.log(base),
+        |_| unreachable!(),
+    )
+}
+fn sin(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    transcendental(&args[0], config, f64::sin, |value| Ok(value.sin()))
+}
+fn cos(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    transcendental(&args[0], config, f64::cos, |value| Ok(value.cos()))
+}
+fn tan(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    transcendental(&args[0], config, f64::tan, |value| {
+        value.tan().ok_or(EvalError::Domain)
+    })
+}
+fn asin(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    transcendental(&args[0], config, f64::asin, |_| Err(EvalError::NotReal))
+}
+fn acos(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    transcendental(&args[0], config, f64::acos, |_| Err(EvalError::NotReal))
+}
+fn atan(args: &[Value], config: &Config) -> Result<Value, EvalError> {
+    transcendental(&args[0], config, f64::atan, |_| Err(EvalError::NotReal))
+}
+/// Applies a function that is only available in floating point.
+///
+/// Floats follow IEEE 754, so `ln(-1)` is NaN. Complex numbers use `complex`
+/// unless they lie on the real axis and `complex` has no answer for them.
+/// Decimals go through `f64` and are rounded back to their scale. The exact
+/// modes have no way to hold the result and report `EvalError::NotExact`.
+fn transcendental(
+    value: &Value,
+    config: &Config,
+    real: impl Fn(f64) -> f64,
+    complex: impl Fn(&Complex) -> Result<Complex, EvalError>,
+) -> Result<Value, EvalError> {
+    match value {
+        Value::Float(value) => Ok(Value::Float(real(*value))),
+        Value::Complex(value) => match complex(value) {
+            Err(EvalError::NotReal) if value.im == 0.0 => {
+                Ok(Value::Complex(Complex::new(real(value.re), 0.0)))
+            }
+            result => result.map(Value::Complex),
+        },
+        Value::Decimal(value) => {
+            let result = Literal::from_f64(real(value.to_f64())).ok_or(EvalError::Domain)?;
+            let config = &config.decimal;
+            Decimal::from_rational(&result.to_rational()?, config.scale, config.rounding)
+                .map(Value::Decimal)
+        }
+        Value::Int(_) | Value::Big(_) | Value::Rational(_) => Err(EvalError::NotExact),
+        Value::Bool(_) => Err(EvalError::ExpectedNumber),
+    }
+}
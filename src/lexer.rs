@@ -0,0 +1,476 @@
+use std::fmt;
+
+use crate::bigint::BigInt;
+use crate::config::{Config, Mode};
+use crate::error::{Error, LexError, Span};
+use crate::literal::Literal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ops {
+    Add,
+    Multiply,
+    Exponent,
+    Subtract,
+    Divide,
+    /// `%`, the remainder of truncating division, with the sign of the
+    /// dividend.
+    Remainder,
+    /// `mod`, the Euclidean remainder, which is never negative.
+    Modulo,
+    /// `//`, division rounded down to an integer.
+    FloorDivide,
+    /// `&`
+    BitAnd,
+    /// `|`
+    BitOr,
+    /// `^^`, since `^` is already the power operator.
+    BitXor,
+    /// `<<`
+    ShiftLeft,
+    /// `>>`, which rounds toward negative infinity like C's `>>` on signed
+    /// integers.
+    ShiftRight,
+    /// `==`
+    Equal,
+    /// `!=`
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    /// `&&`, which skips its right operand when the left one is false.
+    And,
+    /// `||`, which skips its right operand when the left one is true.
+    Or,
+}
+impl fmt::Display for Ops {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Ops::Add => "+",
+            Ops::Multiply => "*",
+            Ops::Exponent => "^",
+            Ops::Subtract => "-",
+            Ops::Divide => "/",
+            Ops::Remainder => "%",
+            Ops::Modulo => "mod",
+            Ops::FloorDivide => "//",
+            Ops::BitAnd => "&",
+            Ops::BitOr => "|",
+            Ops::BitXor => "^^",
+            Ops::ShiftLeft => "<<",
+            Ops::ShiftRight => ">>",
+            Ops::Equal => "==",
+            Ops::NotEqual => "!=",
+            Ops::Less => "<",
+            Ops::LessEqual => "<=",
+            Ops::Greater => ">",
+            Ops::GreaterEqual => ">=",
+            Ops::And => "&&",
+            Ops::Or => "||",
+        })
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Number(Literal),
+    /// An imaginary literal such as `2i`, `1.5j` or a bare `i`, lexed only
+    /// in `Mode::Complex`.
+    Imaginary(Literal),
+    /// A number directly followed by `%`, such as `15%`, unless something
+    /// that can only start an operand follows, which makes `7%3` and
+    /// `7 % (2)` remainders instead.
+    Percent(Literal),
+    /// A variable name such as `rate` or `x_2`. The keywords `mod`, `true`,
+    /// `false`, `if`, `then` and `else` are lexed as other tokens instead.
+    Ident(String),
+    Operator(Ops),
+    /// `=`, only valid after the name at the start of an assignment.
+    Assign,
+    OpenParenth,
+    CloseParenth,
+    /// Separates function call arguments.
+    Comma,
+    /// `true` or `false`.
+    Bool(bool),
+    /// `!`, the postfix factorial or, before an operand, logical not.
+    Bang,
+    /// `!!`, the postfix double factorial. `5!!` is never `(5!)!`.
+    DoubleBang,
+    /// `~`, the prefix bitwise not.
+    Tilde,
+    /// `√`, the prefix square root.
+    Root,
+    /// The keywords of `if c then a else b`.
+    If,
+    Then,
+    Else,
+    /// `?` and `:` of the ternary `c ? a : b`.
+    Question,
+    Colon,
+}
+/// A lexed token together with the byte span it was read from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+pub fn math_lexer(math_expr: &str) -> Result<Vec<Token>, Error> {
+    math_lexer_with(math_expr, &Config::default())
+}
+/// Like `math_lexer`, but honours `Config::strict_ascii` and reads `i` or
+/// `j`, bare or after a number, as the imaginary unit only in
+/// `Mode::Complex`.
+///
+/// Outside strict ASCII, the Unicode operators `×`, `·`, `⋅`, `÷`, `−`, `≠`,
+/// `≤` and `≥` stand for their ASCII spellings, `π` for `pi`, `√` for the
+/// square root and a run of superscripts such as `²` or `⁻¹` for `^` and
+/// its exponent. Unicode whitespace such as a non-breaking space is skipped.
+pub fn math_lexer_with(math_expr: &str, config: &Config) -> Result<Vec<Token>, Error> {
+    let math_expr_bytes = math_expr.as_bytes();
+    let mut tokens = Vec::new();
+    let mut idx: usize = 0;
+    while idx < math_expr_bytes.len() {
+        let start = idx;
+        let c = math_expr_bytes[idx];
+        if let Some(op) = two_char_operator(c, math_expr_bytes.get(idx + 1)) {
+            idx += 2;
+            tokens.push(Token {
+                kind: TokenKind::Operator(op),
+                span: Span::new(start, idx),
+            });
+            continue;
+        }
+        let kind = match c {
+            b'0'..=b'9' | b'.' if c != b'.' || next_is_digit(math_expr_bytes, idx) => {
+                let (literal, mut end) = lex_number(math_expr, start)?;
+                // elsewhere `2i` is an implicit product with the name `i`
+                let kind =
+                    if config.mode == Mode::Complex && is_imaginary_unit(math_expr_bytes, end) {
+                        end += 1;
+                        TokenKind::Imaginary(literal)
+                    } else if is_percent_sign(math_expr, end) {
+                        end += 1;
+                        TokenKind::Percent(literal)
+                    } else {
+                        TokenKind::Number(literal)
+                    };
+                idx = end;
+                tokens.push(Token {
+                    kind,
+                    span: Span::new(start, end),
+                });
+                continue;
+            }
+            // elsewhere `i` and `j` are ordinary names
+            b'i' | b'j'
+                if config.mode == Mode::Complex && is_imaginary_unit(math_expr_bytes, idx) =>
+            {
+                TokenKind::Imaginary(Literal {
+                    digits: BigInt::from(1),
+                    exponent: 0,
+                })
+            }
+            b'a'..=b'z' | b'A'..=b'Z' => {
+                let end = skip_ident(math_expr_bytes, idx);
+                idx = end;
+                let kind = match &math_expr[start..end] {
+                    "mod" => TokenKind::Operator(Ops::Modulo),
+                    "true" => TokenKind::Bool(true),
+                    "false" => TokenKind::Bool(false),
+                    "if" => TokenKind::If,
+                    "then" => TokenKind::Then,
+                    "else" => TokenKind::Else,
+                    name => TokenKind::Ident(name.to_string()),
+                };
+                tokens.push(Token {
+                    kind,
+                    span: Span::new(start, end),
+                });
+                continue;
+            }
+            b'=' => TokenKind::Assign,
+            b'(' => TokenKind::OpenParenth,
+            b')' => TokenKind::CloseParenth,
+            b',' => TokenKind::Comma,
+            b'!' if math_expr_bytes.get(idx + 1) == Some(&b'!') => {
+                idx += 2;
+                tokens.push(Token {
+                    kind: TokenKind::DoubleBang,
+                    span: Span::new(start, idx),
+                });
+                continue;
+            }
+            b'!' => TokenKind::Bang,
+            b'*' => TokenKind::Operator(Ops::Multiply),
+            b'/' => TokenKind::Operator(Ops::Divide),
+            b'&' => TokenKind::Operator(Ops::BitAnd),
+            b'|' => TokenKind::Operator(Ops::BitOr),
+            b'~' => TokenKind::Tilde,
+            b'?' => TokenKind::Question,
+            b':' => TokenKind::Colon,
+            b'%' => TokenKind::Operator(Ops::Remainder),
+            b'^' => TokenKind::Operator(Ops::Exponent),
+            b'<' => TokenKind::Operator(Ops::Less),
+            b'>' => TokenKind::Operator(Ops::Greater),
+            b'+' => TokenKind::Operator(Ops::Add),
+            b'-' => TokenKind::Operator(Ops::Subtract),
+            b' ' | b'\t' => {
+                idx += 1;
+                continue;
+            }
+            _ if !c.is_ascii() && !config.strict_ascii => {
+                idx = lex_unicode(math_expr, start, &mut tokens)?;
+                continue;
+            }
+            _ => {
+                let len = math_expr[idx..].chars().next().map_or(1, char::len_utf8);
+                return Err(Error::Lex(
+                    LexError::UnknownToken,
+                    Span::new(idx, idx + len),
+                ));
+            }
+        };
+        idx += 1;
+        tokens.push(Token {
+            kind,
+            span: Span::new(start, idx),
+        });
+    }
+    Ok(tokens)
+}
+/// Lexes the non-ASCII character at `start`, which may begin a run of
+/// superscripts, and returns the index just past what it consumed.
+fn lex_unicode(math_expr: &str, start: usize, tokens: &mut Vec<Token>) -> Result<usize, Error> {
+    let c = math_expr[start..]
+        .chars()
+        .next()
+        .expect("the lexer only stops at character boundaries");
+    let end = start + c.len_utf8();
+    let kind = match c {
+        '×' | '·' | '⋅' => TokenKind::Operator(Ops::Multiply),
+        '÷' => TokenKind::Operator(Ops::Divide),
+        '−' => TokenKind::Operator(Ops::Subtract),
+        '≠' => TokenKind::Operator(Ops::NotEqual),
+        '≤' => TokenKind::Operator(Ops::LessEqual),
+        '≥' => TokenKind::Operator(Ops::GreaterEqual),
+        '√' => TokenKind::Root,
+        'π' => TokenKind::Ident("pi".to_string()),
+        c if superscript(c).is_some() => return lex_superscript(math_expr, start, tokens),
+        c if c.is_whitespace() => return Ok(end),
+        _ => return Err(Error::Lex(LexError::UnknownToken, Span::new(start, end))),
+    };
+    tokens.push(Token {
+        kind,
+        span: Span::new(start, end),
+    });
+    Ok(end)
+}
+/// The ASCII character a superscript digit or minus sign stands for.
+fn superscript(c: char) -> Option<char> {
+    Some(match c {
+        '⁰' => '0',
+        '¹' => '1',
+        '²' => '2',
+        '³' => '3',
+        '⁴' => '4',
+        '⁵' => '5',
+        '⁶' => '6',
+        '⁷' => '7',
+        '⁸' => '8',
+        '⁹' => '9',
+        '⁻' => '-',
+        _ => return None,
+    })
+}
+/// Lexes a run of superscripts such as `²` or `⁻¹²` as `^`, spanning the
+/// whole run, followed by the exponent it spells. The minus sign may only
+/// come first.
+fn lex_superscript(math_expr: &str, start: usize, tokens: &mut Vec<Token>) -> Result<usize, Error> {
+    let mut end = start;
+    let mut run = String::new();
+    for c in math_expr[start..].chars() {
+        let Some(ascii) = superscript(c) else { break };
+        run.push(ascii);
+        end += c.len_utf8();
+    }
+    let digits = run.strip_prefix('-').unwrap_or(&run);
+    if digits.is_empty() || digits.contains('-') {
+        return Err(Error::Lex(LexError::MalformedNumber, Span::new(start, end)));
+    }
+    tokens.push(Token {
+        kind: TokenKind::Operator(Ops::Exponent),
+        span: Span::new(start, end),
+    });
+    let mut digits_start = start;
+    if run.starts_with('-') {
+        digits_start += '⁻'.len_utf8();
+        tokens.push(Token {
+            kind: TokenKind::Operator(Ops::Subtract),
+            span: Span::new(start, digits_start),
+        });
+    }
+    let literal = Literal {
+        digits: BigInt::parse_digits(digits).expect("a run of ascii digits"),
+        exponent: 0,
+    };
+    tokens.push(Token {
+        kind: TokenKind::Number(literal),
+        span: Span::new(digits_start, end),
+    });
+    Ok(end)
+}
+/// Operators spelled with two characters, such as `<=` and `//`, which take
+/// precedence over the one-character operators they start with.
+fn two_char_operator(first: u8, second: Option<&u8>) -> Option<Ops> {
+    Some(match (first, second?) {
+        (b'/', b'/') => Ops::FloorDivide,
+        (b'^', b'^') => Ops::BitXor,
+        (b'<', b'<') => Ops::ShiftLeft,
+        (b'>', b'>') => Ops::ShiftRight,
+        (b'=', b'=') => Ops::Equal,
+        (b'!', b'=') => Ops::NotEqual,
+        (b'<', b'=') => Ops::LessEqual,
+        (b'>', b'=') => Ops::GreaterEqual,
+        (b'&', b'&') => Ops::And,
+        (b'|', b'|') => Ops::Or,
+        _ => return None,
+    })
+}
+/// Whether an `i` or `j` standing on its own starts at `idx`.
+fn is_imaginary_unit(bytes: &[u8], idx: usize) -> bool {
+    matches!(bytes.get(idx), Some(b'i' | b'j'))
+        && !bytes
+            .get(idx + 1)
+            .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+}
+/// Whether a `%` at `idx` ends a percent literal rather than starting a
+/// remainder. It is a remainder when the next character past any whitespace
+/// can only begin an operand: a digit, a name, `(`, `~`, `!`, `√` or `π`.
+/// A sign or a keyword such as `mod` or `else` is read as the operator that
+/// follows the percentage, so `200 + 15% - 10` is `220`.
+fn is_percent_sign(math_expr: &str, idx: usize) -> bool {
+    let Some(rest) = math_expr[idx..].strip_prefix('%') else {
+        return false;
+    };
+    let next = rest.trim_start();
+    let word = &next[..skip_ident(next.as_bytes(), 0)];
+    if matches!(word, "mod" | "then" | "else") || next.starts_with("!=") {
+        return true;
+    }
+    !next.starts_with(|c: char| {
+        c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '(' | '~' | '!' | '√' | 'π')
+    })
+}
+/// Identifiers start with a letter and continue with letters, digits or `_`.
+fn skip_ident(bytes: &[u8], mut idx: usize) -> usize {
+    while idx < bytes.len() && (bytes[idx].is_ascii_alphanumeric() || bytes[idx] == b'_') {
+        idx += 1;
+    }
+    idx
+}
+fn next_is_digit(bytes: &[u8], idx: usize) -> bool {
+    bytes.get(idx + 1).is_some_and(u8::is_ascii_digit)
+}
+/// Skips a run of digits, which may be separated by single underscores as
+/// in `1_000_000`.
+fn skip_digits(bytes: &[u8], mut idx: usize) -> usize {
+    while idx < bytes.len() && (bytes[idx].is_ascii_digit() || bytes[idx] == b'_') {
+        idx += 1;
+    }
+    idx
+}
+/// The digits in `math_expr[start..end]` without their separators. An
+/// underscore must sit between two digits.
+fn digit_run(math_expr: &str, start: usize, end: usize) -> Result<String, Error> {
+    let run = &math_expr[start..end];
+    if run.starts_with('_') || run.ends_with('_') || run.contains("__") {
+        return Err(Error::Lex(LexError::MalformedNumber, Span::new(start, end)));
+    }
+    Ok(run.replace('_', ""))
+}
+/// Lexes `0xFF`, `0b1010` and `0o17` style literals starting at `start`,
+/// or returns `None` if there is no radix prefix.
+fn lex_radix_number(math_expr: &str, start: usize) -> Option<Result<(Literal, usize), Error>> {
+    let bytes = math_expr.as_bytes();
+    let radix = match (bytes[start], bytes.get(start + 1)) {
+        (b'0', Some(b'x' | b'X')) => 16,
+        (b'0', Some(b'b' | b'B')) => 2,
+        (b'0', Some(b'o' | b'O')) => 8,
+        _ => return None,
+    };
+    let digits_start = start + 2;
+    let end = skip_ident(bytes, digits_start);
+    let malformed = Error::Lex(LexError::MalformedNumber, Span::new(start, end));
+    let digits = match digit_run(math_expr, digits_start, end) {
+        Ok(digits) if !digits.is_empty() => digits,
+        _ => return Some(Err(malformed)),
+    };
+    let mut value = BigInt::zero();
+    for digit in digits.chars() {
+        let Some(digit) = digit.to_digit(radix) else {
+            return Some(Err(malformed));
+        };
+        value = &(&value * &BigInt::from(radix as i64)) + &BigInt::from(digit as i64);
+    }
+    let literal = Literal {
+        digits: value,
+        exponent: 0,
+    };
+    Some(Ok((literal, end)))
+}
+/// Lexes `123`, `1.5`, `.5` and `1.5e-3` style literals starting at `start`,
+/// returning the literal and the index just past it.
+fn lex_number(math_expr: &str, start: usize) -> Result<(Literal, usize), Error> {
+    if let Some(result) = lex_radix_number(math_expr, start) {
+        return result;
+    }
+    let bytes = math_expr.as_bytes();
+    let int_end = skip_digits(bytes, start);
+    let int_part = &digit_run(math_expr, start, int_end)?;
+    if int_part.len() > 1 && int_part.starts_with('0') {
+        return Err(Error::Lex(LexError::LeadingZero, Span::new(start, int_end)));
+    }
+    let mut idx = int_end;
+    let mut frac_part = String::new();
+    if bytes.get(idx) == Some(&b'.') {
+        if !next_is_digit(bytes, idx) {
+            return Err(Error::Lex(
+                LexError::MalformedNumber,
+                Span::new(start, idx + 1),
+            ));
+        }
+        let frac_end = skip_digits(bytes, idx + 1);
+        frac_part = digit_run(math_expr, idx + 1, frac_end)?;
+        idx = frac_end;
+    }
+    let mut exponent: i64 = 0;
+    if let Some(b'e' | b'E') = bytes.get(idx) {
+        // only an exponent if digits follow, so `2e` is left for whatever
+        // comes after the number
+        let sign_len = match bytes.get(idx + 1) {
+            Some(b'+' | b'-') => 1,
+            _ => 0,
+        };
+        let exp_start = idx + 1 + sign_len;
+        if bytes.get(exp_start).is_some_and(u8::is_ascii_digit) {
+            let exp_end = skip_digits(bytes, exp_start);
+            let magnitude = digit_run(math_expr, exp_start, exp_end)?
+                .parse::<i64>()
+                .unwrap_or(i64::MAX / 4)
+                .min(i64::MAX / 4);
+            exponent = if bytes[idx + 1] == b'-' {
+                -magnitude
+            } else {
+                magnitude
+            };
+            idx = exp_end;
+        }
+    }
+    let digits =
+        BigInt::parse_digits(&format!("{}{}", int_part, frac_part)).expect("a run of ascii digits");
+    let literal = Literal {
+        digits,
+        exponent: exponent - frac_part.len() as i64,
+    };
+    Ok((literal, idx))
+}
@@ -0,0 +1,1656 @@
+//! Lexing, parsing and evaluation of math expressions.
+//!
+//! The pipeline is `math_lexer` -> `math_parse` -> `traverse_expr_tree`;
+//! `evaluate` runs all three stages on a string in one call.
+mod ast;
+mod bigint;
+mod complex;
+mod config;
+mod constants;
+mod context;
+mod decimal;
+mod diagnostic;
+mod environment;
+mod error;
+mod eval;
+mod format;
+mod functions;
+mod lexer;
+mod literal;
+mod parser;
+mod rational;
+mod value;
+
+pub use ast::{Expr, Statement, UnaryOp};
+pub use bigint::BigInt;
+pub use complex::Complex;
+pub use config::{
+    Associativity, Config, DecimalConfig, ImplicitMultiplication, Mode, Overflow, Percent, Rounding,
+};
+pub use constants::constant_names;
+pub use context::{BoxError, Context, HostError};
+pub use decimal::Decimal;
+pub use diagnostic::render_diagnostic;
+pub use environment::{Environment, Function};
+pub use error::{Error, EvalError, LexError, ParseError, Span};
+pub use eval::{
+    execute, traverse_expr_tree, traverse_expr_tree_in, traverse_expr_tree_with, MAX_EVAL_DEPTH,
+};
+pub use format::{format_value, ComplexStyle, Format, Radix, RationalStyle};
+pub use functions::{builtin_names, Arity};
+pub use lexer::{math_lexer, math_lexer_with, Ops, Token, TokenKind};
+pub use literal::Literal;
+pub use parser::{
+    get_associativity, get_precedence, math_parse, math_parse_statement, math_parse_with,
+    MAX_NESTING,
+};
+pub use rational::Rational;
+pub use value::Value;
+
+use context::HostFunctions;
+use eval::evaluate_statement;
+
+/// Lexes, parses and evaluates `input`.
+pub fn evaluate(input: &str) -> Result<Value, Error> {
+    evaluate_with(input, &Config::default())
+}
+/// Like `evaluate`, but with non-default `config`.
+pub fn evaluate_with(input: &str, config: &Config) -> Result<Value, Error> {
+    let tokens = math_lexer_with(input, config)?;
+    let expr = math_parse_with(&tokens, config)?;
+    traverse_expr_tree_with(&expr, config)
+}
+/// Like `evaluate_with`, but `input` may be an assignment such as
+/// `rate = 7`, and variables and functions are read from and stored in
+/// `env`. A function definition is `ParseError::UnexpectedDefinition`; run
+/// those with `run_in`.
+pub fn evaluate_in(input: &str, config: &Config, env: &mut Environment) -> Result<Value, Error> {
+    let tokens = math_lexer_with(input, config)?;
+    let statement = math_parse_statement(&tokens, config)?;
+    evaluate_statement(&statement, config, env, &HostFunctions::new())
+}
+/// Like `evaluate_in`, but `input` may also define a function such as
+/// `f(x) = x^2`, which returns `None`.
+pub fn run_in(input: &str, config: &Config, env: &mut Environment) -> Result<Option<Value>, Error> {
+    let tokens = math_lexer_with(input, config)?;
+    let statement = math_parse_statement(&tokens, config)?;
+    execute(&statement, config, env)
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::identity_op,
+    clippy::neg_multiply,
+    clippy::single_match,
+    double_negations
+)]
+mod tests {
+    use super::*;
+    #[test]
+    fn five_pow_five_times_four_minus_three() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("5 ^ 5 * 4 - 3")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 5i64.pow(5) * 4 - 3);
+        Ok(())
+    }
+    #[test]
+    fn four_plus_four() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("4 + 4")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 4 + 4);
+        Ok(())
+    }
+    #[test]
+    fn five_times_four_minus_three() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("5 * 4 - 3")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 5 * 4 - 3);
+        Ok(())
+    }
+    #[test]
+    fn four_minus_four_times_three() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("4 - 4 * 3")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 4 - 4 * 3);
+        Ok(())
+    }
+    #[test]
+    fn three_divide_three() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("3 / 3")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 3 / 3);
+        Ok(())
+    }
+    #[test]
+    fn six_power_two_minus_four_times_three() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("6 ^ 2 - 4 * 3")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 6i64.pow(2) - 4 * 3);
+        Ok(())
+    }
+    #[test]
+    fn two_power_two_power_two() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("2 ^ 2 ^ 2")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 2i64.pow(2u32.pow(2)));
+        Ok(())
+    }
+    #[test]
+    fn two_power_two_divide_two() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("2 ^ 2 / 2")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 2i64.pow(2) / 2);
+        Ok(())
+    }
+    #[test]
+    fn three_minus_four_times_five_add_3_power_two() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("3 - 4 * 5 + 3 ^ 2")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 3 - 4 * 5 + 3i64.pow(2));
+        Ok(())
+    }
+    #[test]
+    fn p_four_minus_three_p_times_p_three_minus_5_p() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("(4 - 3 ) * (3 - 5)")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == (4 - 3) * (3 - 5));
+        Ok(())
+    }
+    #[test]
+    fn p_p_p_one_plus_one_p_p_p() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("(((1 + 1)))")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == (1 + 1));
+        Ok(())
+    }
+    #[test]
+    fn p_p_one_plus_three_p_times_p_four_plus_five_p_p() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("((1 + 3) * (4 + 5))")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == ((1 + 3) * (4 + 5)));
+        Ok(())
+    }
+    #[test]
+    fn unmatched_parentheses() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("((1) + 1")?;
+        let res = math_parse(&tokens);
+        match res {
+            Err(_) => {}
+            Ok(_) => return Err("Unmatched parentheses was not caught".into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn extra_closing_parentheses() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("(1 + 1))))))))))))))))))))))))))))))))))))))")?;
+        let res = math_parse(&tokens);
+        match res {
+            Err(_) => {}
+            Ok(_) => return Err("Unmatched parentheses was not caught".into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn unknown_token() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("1 _ 1");
+        match tokens {
+            Err(_) => {}
+            Ok(_) => return Err("Unknown token wasn't caught".into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn leading_zeroes() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("01 + 1");
+        match tokens {
+            Err(_) => {}
+            Ok(_) => return Err("leading zero wasn't caught".into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn neg_one_minus_one() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("-1-1")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == -1 - 1);
+        Ok(())
+    }
+    #[test]
+    fn one_minus_neg_one() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("1--1")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 1 - -1);
+        Ok(())
+    }
+    #[test]
+    fn incorrect_unary_op() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("1-*1")?;
+        let res = math_parse(&tokens);
+        match res {
+            Err(_) => {}
+            Ok(_) => return Err("incorrect unary op was not caught".into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn one_times_neg_neg_one() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("1*--1")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 1 * --1);
+        Ok(())
+    }
+    #[test]
+    fn five_times_neg_one_times_p_one_plus_four_p() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("5 * -1 * (1 + 4)")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 5 * -1 * (1 + 4));
+        Ok(())
+    }
+    #[test]
+    fn one_times_neg_neg_neg_neg_neg_one() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("1 * -----1")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 1 * -----1);
+        Ok(())
+    }
+    #[test]
+    fn one_times_pos_one() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("1 * +1")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == 1 * (0 + 1));
+        Ok(())
+    }
+    #[test]
+    fn one_times_times_neg_one() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("1**-1")?;
+        let res = math_parse(&tokens);
+        match res {
+            Err(_) => {}
+            Ok(_) => return Err("incorrect unary op was not caught".into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn neg_one_times_mult_one() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("-1 * *1")?;
+        let res = math_parse(&tokens);
+        match res {
+            Err(_) => {}
+            Ok(_) => return Err("incorrect unary op was not caught".into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn neg_one_times_p_one_plus_four_time_four_p() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("-1 * ( 1 + 4 * 4)")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == -1 * (1 + 4 * 4));
+        Ok(())
+    }
+    #[test]
+    fn expr2_in_expr1_times_expr2_in_expr1() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("(1 * -1) * (2 * -2)")?;
+        let expr = math_parse(&tokens)?;
+        assert!(traverse_expr_tree(&expr)? == (1 * -1) * (2 * -2));
+        Ok(())
+    }
+    #[test]
+    fn single_zero() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("1 + 0")?;
+        let expr = math_parse(&tokens)?;
+        let eval_res = traverse_expr_tree(&expr);
+        match eval_res {
+            Ok(_) => {}
+            Err(_) => return Err("single zeroes should be allowed".into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn divide_by_zero() -> Result<(), Box<dyn std::error::Error>> {
+        let tokens = math_lexer("1 / 0")?;
+        let expr = math_parse(&tokens)?;
+        let eval_res = traverse_expr_tree(&expr);
+        match eval_res {
+            Ok(_) => return Err("divide by zero not caught".into()),
+            Err(_) => {}
+        }
+        Ok(())
+    }
+    #[test]
+    fn evaluate_runs_whole_pipeline() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("(1 + 3) * 4")? == 16);
+        match evaluate("1 +") {
+            Err(_) => {}
+            Ok(_) => return Err("missing operand was not caught".into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn errors_carry_kind_and_span() {
+        assert!(evaluate("1 _ 1") == Err(Error::Lex(LexError::UnknownToken, Span::new(2, 3))));
+        assert!(
+            evaluate("(1 + 1")
+                == Err(Error::Parse(
+                    ParseError::ExpectedCloseParenth,
+                    Span::new(6, 6)
+                ))
+        );
+        assert!(
+            evaluate(") + 1")
+                == Err(Error::Parse(
+                    ParseError::UnexpectedCloseParenth,
+                    Span::new(0, 1)
+                ))
+        );
+        assert!(evaluate("4 / 0") == Err(Error::Eval(EvalError::DivideByZero, Span::new(2, 3))));
+    }
+    #[test]
+    fn huge_literal_overflows_integer_mode() {
+        assert!(
+            evaluate("99999999999999999999")
+                == Err(Error::Eval(EvalError::Overflow, Span::new(0, 20)))
+        );
+    }
+    #[test]
+    fn diagnostic_underlines_offending_token() {
+        let input = "(1 + 22) 33";
+        let err = evaluate(input).unwrap_err();
+        assert!(
+            render_diagnostic(input, &err)
+                == "error: expected operator.\n  (1 + 22) 33\n           ^~ unexpected token\n  hint: did you forget an operator?"
+        );
+    }
+    #[test]
+    fn overflow_diagnostic_states_the_digit_limit() {
+        let input = "10^200000";
+        let err = eval_in_mode(Mode::BigInt, input).unwrap_err();
+        assert!(render_diagnostic(input, &err).contains("^ result is too large"));
+        assert!(render_diagnostic(input, &err).contains("up to 100000 digits"));
+    }
+    #[test]
+    fn five_plus_p_five_p_times_five() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("5 + (5) * 5")? == 5 + 5 * 5);
+        Ok(())
+    }
+    #[test]
+    fn unary_minus_binds_looser_than_power() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("-2 ^ 2")? == -4);
+        assert!(evaluate("2 * -3 ^ 2")? == 2 * -(3i64.pow(2)));
+        Ok(())
+    }
+    #[test]
+    fn trailing_close_parenthesis() {
+        assert!(
+            evaluate("1 + 1)")
+                == Err(Error::Parse(
+                    ParseError::UnexpectedCloseParenth,
+                    Span::new(5, 6)
+                ))
+        );
+    }
+    #[test]
+    fn empty_expression() {
+        assert!(evaluate(" ") == Err(Error::Parse(ParseError::EmptyExpression, Span::new(0, 0))));
+    }
+    #[test]
+    fn long_chain_of_additions() -> Result<(), Box<dyn std::error::Error>> {
+        let input = vec!["1"; 1_000].join(" + ");
+        assert!(evaluate(&input)? == 1_000);
+        Ok(())
+    }
+    #[test]
+    fn very_long_chains_do_not_overflow_the_stack() -> Result<(), Box<dyn std::error::Error>> {
+        let sum = vec!["1"; 100_000].join(" + ");
+        assert!(evaluate(&sum)? == 100_000);
+        let expr = math_parse(&math_lexer(&sum)?)?;
+        assert!(expr.clone().to_string() == sum);
+        let factorials = format!("-0{}", "!".repeat(100_000));
+        assert!(evaluate(&factorials)? == -1);
+        assert!(math_parse(&math_lexer(&factorials)?)?.to_string() == factorials);
+        assert!(evaluate(&vec!["true"; 100_000].join(" && "))? == Value::Bool(true));
+        Ok(())
+    }
+    #[test]
+    fn deep_nesting_is_an_error() -> Result<(), Box<dyn std::error::Error>> {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(evaluate(&nested(MAX_NESTING - 1))? == 1);
+        assert!(
+            evaluate(&nested(1_000))
+                == Err(Error::Parse(
+                    ParseError::NestingLimit,
+                    Span::new(MAX_NESTING, MAX_NESTING + 1)
+                ))
+        );
+        let negated = format!("{}1", "-".repeat(1_000));
+        assert!(
+            evaluate(&negated)
+                == Err(Error::Parse(
+                    ParseError::NestingLimit,
+                    Span::new(MAX_NESTING, MAX_NESTING + 1)
+                ))
+        );
+        Ok(())
+    }
+    #[test]
+    fn exponent_is_right_associative() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("2 ^ 3 ^ 2")? == 512);
+        Ok(())
+    }
+    #[test]
+    fn exponent_can_be_left_associative() -> Result<(), Box<dyn std::error::Error>> {
+        let mut config = Config::default();
+        config.set_associativity(Ops::Exponent, Associativity::Left);
+        assert!(evaluate_with("2 ^ 3 ^ 2", &config)? == 64);
+        assert!(evaluate_with("2 * 3 ^ 2 ^ 2", &config)? == 2 * 3i64.pow(2).pow(2));
+        Ok(())
+    }
+    #[test]
+    fn subtraction_can_be_right_associative() -> Result<(), Box<dyn std::error::Error>> {
+        let mut config = Config::default();
+        config.set_associativity(Ops::Subtract, Associativity::Right);
+        assert!(evaluate_with("10 - 4 - 3", &config)? == 10 - (4 - 3));
+        Ok(())
+    }
+    #[test]
+    fn parse_builds_unary_binary_and_group_nodes() -> Result<(), Box<dyn std::error::Error>> {
+        let expr = math_parse(&math_lexer("-(1 + 2)")?)?;
+        match &expr {
+            Expr::Unary {
+                op: UnaryOp::Minus,
+                operand,
+                ..
+            } => match &**operand {
+                Expr::Group { inner, span } => {
+                    assert!(*span == Span::new(1, 8));
+                    assert!(matches!(**inner, Expr::Binary { op: Ops::Add, .. }));
+                }
+                other => return Err(format!("expected group, got {:?}", other).into()),
+            },
+            other => return Err(format!("expected unary minus, got {:?}", other).into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn overflow_is_an_error() {
+        assert!(evaluate("9 ^ 99") == Err(Error::Eval(EvalError::Overflow, Span::new(2, 3))));
+        assert!(matches!(
+            evaluate("9223372036854775807 + 1"),
+            Err(Error::Eval(EvalError::Overflow, _))
+        ));
+        assert!(matches!(
+            evaluate("(0 - 9223372036854775807 - 1) / -1"),
+            Err(Error::Eval(EvalError::Overflow, _))
+        ));
+        assert!(matches!(
+            evaluate("2 ^ 99999999999"),
+            Err(Error::Eval(EvalError::Overflow, _))
+        ));
+    }
+    #[test]
+    fn huge_exponent_of_trivial_base() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("1 ^ 99999999999")? == 1);
+        assert!(evaluate("(-1) ^ 99999999999")? == -1);
+        assert!(evaluate("0 ^ 99999999999")? == 0);
+        Ok(())
+    }
+    #[test]
+    fn wrapping_overflow() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config {
+            overflow: Overflow::Wrapping,
+            ..Config::default()
+        };
+        assert!(evaluate_with("9223372036854775807 + 1", &config)? == i64::MIN);
+        assert!(evaluate_with("3 ^ 99", &config)? == 3i64.wrapping_pow(99));
+        Ok(())
+    }
+    #[test]
+    fn saturating_overflow() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config {
+            overflow: Overflow::Saturating,
+            ..Config::default()
+        };
+        assert!(evaluate_with("9 ^ 99", &config)? == i64::MAX);
+        assert!(evaluate_with("(-9) ^ 99", &config)? == i64::MIN);
+        assert!(evaluate_with("-9223372036854775807 - 9", &config)? == i64::MIN);
+        Ok(())
+    }
+    fn eval_in_mode(mode: Mode, input: &str) -> Result<Value, Error> {
+        let config = Config {
+            mode,
+            ..Config::default()
+        };
+        evaluate_with(input, &config)
+    }
+    #[test]
+    fn big_integer_literals() -> Result<(), Box<dyn std::error::Error>> {
+        let digits = "1234567890123456789012345678901234567890";
+        assert!(eval_in_mode(Mode::BigInt, digits)?.to_string() == digits);
+        assert!(eval_in_mode(Mode::BigInt, &format!("{} - {}", digits, digits))? == 0);
+        Ok(())
+    }
+    #[test]
+    fn big_integer_powers() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(
+            eval_in_mode(Mode::BigInt, "2 ^ 512")?.to_string()
+                == "13407807929942597099574024998205846127479365820592393377723561443721764030073546976801874298166903427690031858186486050853753882811946569946433649006084096"
+        );
+        assert!(
+            eval_in_mode(Mode::BigInt, "-(2 ^ 127) / 2 ^ 63")?.to_string()
+                == "-18446744073709551616"
+        );
+        Ok(())
+    }
+    #[test]
+    fn big_integer_division_truncates() -> Result<(), Box<dyn std::error::Error>> {
+        let quotient = eval_in_mode(
+            Mode::BigInt,
+            "123456789012345678901234567890 / 987654321987654321",
+        )?;
+        assert!(quotient.to_string() == "124999998748");
+        assert!(eval_in_mode(Mode::BigInt, "-7 / 2")? == -7 / 2);
+        Ok(())
+    }
+    #[test]
+    fn big_integer_division_by_zero() {
+        assert!(
+            eval_in_mode(Mode::BigInt, "7 / 0")
+                == Err(Error::Eval(EvalError::DivideByZero, Span::new(2, 3)))
+        );
+    }
+    #[test]
+    fn big_integer_power_limits() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::BigInt, "(-1) ^ 99999999999999999999")? == -1);
+        assert!(eval_in_mode(Mode::BigInt, "(-1) ^ 99999999999999999998")? == 1);
+        assert!(matches!(
+            eval_in_mode(Mode::BigInt, "10 ^ 99999999"),
+            Err(Error::Eval(EvalError::Overflow, _))
+        ));
+        Ok(())
+    }
+    #[test]
+    fn decimal_and_scientific_literals() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::Float, "3.25")? == Value::Float(3.25));
+        assert!(eval_in_mode(Mode::Float, ".5 + 0.25")? == Value::Float(0.75));
+        assert!(eval_in_mode(Mode::Float, "1e-9")? == Value::Float(1e-9));
+        assert!(eval_in_mode(Mode::Float, "2.5E+3")? == Value::Float(2500.0));
+        assert!(eval_in_mode(Mode::Float, "0.1 + 0.2")? == Value::Float(0.1 + 0.2));
+        Ok(())
+    }
+    #[test]
+    fn float_division_follows_ieee() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::Float, "7 / 2")? == Value::Float(3.5));
+        assert!(eval_in_mode(Mode::Float, "1 / 0")? == Value::Float(f64::INFINITY));
+        assert!(eval_in_mode(Mode::Float, "2 ^ -1")? == Value::Float(0.5));
+        Ok(())
+    }
+    #[test]
+    fn float_zero_by_zero_is_nan() -> Result<(), Box<dyn std::error::Error>> {
+        match eval_in_mode(Mode::Float, "0 / 0")? {
+            Value::Float(value) => assert!(value.is_nan()),
+            other => return Err(format!("expected NaN, got {}", other).into()),
+        }
+        Ok(())
+    }
+    #[test]
+    fn malformed_numbers() {
+        assert!(evaluate("1. + 2") == Err(Error::Lex(LexError::MalformedNumber, Span::new(0, 2))));
+        assert!(evaluate("00.5") == Err(Error::Lex(LexError::LeadingZero, Span::new(0, 2))));
+        assert!(evaluate(". + 1") == Err(Error::Lex(LexError::UnknownToken, Span::new(0, 1))));
+    }
+    #[test]
+    fn fractional_literals_in_integer_mode() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("2.50e1")? == 25);
+        assert!(evaluate("1e3 + 0.0")? == 1000);
+        assert!(evaluate("1 + 2.5") == Err(Error::Eval(EvalError::NotAnInteger, Span::new(4, 7))));
+        Ok(())
+    }
+    #[test]
+    fn float_precision() -> Result<(), Box<dyn std::error::Error>> {
+        let format = Format {
+            precision: Some(3),
+            ..Format::default()
+        };
+        assert!(format_value(&eval_in_mode(Mode::Float, "2 / 3")?, &format) == "0.667");
+        assert!(format_value(&eval_in_mode(Mode::Float, "0.1 + 0.2")?, &format) == "0.3");
+        assert!(format_value(&eval_in_mode(Mode::Float, "-0.0001")?, &format) == "0");
+        assert!(format_value(&eval_in_mode(Mode::Float, "1 / 0")?, &format) == "inf");
+        assert!(
+            format_value(&eval_in_mode(Mode::Float, "0.1 + 0.2")?, &Format::default())
+                == "0.30000000000000004"
+        );
+        Ok(())
+    }
+    #[test]
+    fn rational_arithmetic_is_exact() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::Rational, "1 / 3 + 1 / 6")?.to_string() == "1/2");
+        assert!(eval_in_mode(Mode::Rational, "1 / 3 * 3")? == 1);
+        assert!(eval_in_mode(Mode::Rational, "-4 / 6")?.to_string() == "-2/3");
+        Ok(())
+    }
+    #[test]
+    fn rational_decimal_literals() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::Rational, "0.1 + 0.2")?.to_string() == "3/10");
+        Ok(())
+    }
+    #[test]
+    fn rational_negative_powers() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::Rational, "(2 / 3) ^ -2")?.to_string() == "9/4");
+        Ok(())
+    }
+    #[test]
+    fn rational_errors() {
+        assert!(
+            eval_in_mode(Mode::Rational, "1 / (1 - 1)")
+                == Err(Error::Eval(EvalError::DivideByZero, Span::new(2, 3)))
+        );
+        assert!(
+            eval_in_mode(Mode::Rational, "4 ^ 0.5")
+                == Err(Error::Eval(EvalError::NotAnInteger, Span::new(2, 3)))
+        );
+    }
+    #[test]
+    fn rational_mixed_display() -> Result<(), Box<dyn std::error::Error>> {
+        let format = Format {
+            rational: RationalStyle::Mixed,
+            ..Format::default()
+        };
+        assert!(format_value(&eval_in_mode(Mode::Rational, "-7 / 2")?, &format) == "-3 1/2");
+        assert!(format_value(&eval_in_mode(Mode::Rational, "1 / 2")?, &format) == "1/2");
+        Ok(())
+    }
+    #[test]
+    fn rational_decimal_display() -> Result<(), Box<dyn std::error::Error>> {
+        let mut format = Format {
+            rational: RationalStyle::Decimal,
+            ..Format::default()
+        };
+        assert!(format_value(&eval_in_mode(Mode::Rational, "-7 / 2")?, &format) == "-3.5");
+        assert!(
+            format_value(&eval_in_mode(Mode::Rational, "2 / 3")?, &format) == "0.666666666666667"
+        );
+        format.precision = Some(2);
+        assert!(format_value(&eval_in_mode(Mode::Rational, "1 / 3")?, &format) == "0.33");
+        Ok(())
+    }
+    #[test]
+    fn complex_arithmetic() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::Complex, "(1 + 2i) * (3 - i)")?.to_string() == "5+5i");
+        assert!(eval_in_mode(Mode::Complex, "1 / (2 - 2i)")?.to_string() == "0.25+0.25i");
+        assert!(eval_in_mode(Mode::Complex, "2.5j - 1")?.to_string() == "-1+2.5i");
+        assert!(eval_in_mode(Mode::Complex, "1 - i")?.to_string() == "1-i");
+        Ok(())
+    }
+    #[test]
+    fn imaginary_unit_squares_to_minus_one() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::Complex, "i ^ 2")? == -1);
+        assert!(eval_in_mode(Mode::Complex, "j * j")? == -1);
+        Ok(())
+    }
+    #[test]
+    fn complex_roots_of_negative_numbers() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::Complex, "(-1) ^ 0.5")?.to_string() == "i");
+        assert!(eval_in_mode(Mode::Complex, "(-4) ^ 0.5")?.to_string() == "2i");
+        Ok(())
+    }
+    #[test]
+    fn complex_division_by_zero() {
+        assert!(
+            eval_in_mode(Mode::Complex, "1 / (0i)")
+                == Err(Error::Eval(EvalError::DivideByZero, Span::new(2, 3)))
+        );
+    }
+    #[test]
+    fn i_is_a_name_outside_complex_mode() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        context.evaluate("i = 2")?;
+        context.run("f(j) = j * i")?;
+        assert!(context.evaluate("f(3) + i")? == Value::Int(8));
+        assert!(context.evaluate("2i")? == Value::Int(4));
+        Ok(())
+    }
+    #[test]
+    fn imaginary_literals_need_complex_mode() -> Result<(), Box<dyn std::error::Error>> {
+        // `2i` multiplies 2 by the name `i`, and `2ix` by the name `ix`
+        assert!(
+            evaluate("1 + 2i") == Err(Error::Eval(EvalError::UndefinedVariable, Span::new(5, 6)))
+        );
+        assert!(evaluate("2ix") == Err(Error::Eval(EvalError::UndefinedVariable, Span::new(1, 3))));
+        // a literal defined in complex mode is rejected after leaving it
+        let mut context = Context::new();
+        context.config.mode = Mode::Complex;
+        context.run("f(x) = x + 2i")?;
+        context.config.mode = Mode::Integer;
+        assert!(
+            context.evaluate("f(1)")
+                == Err(Error::Eval(EvalError::ImaginaryNumber, Span::new(0, 4)))
+        );
+        Ok(())
+    }
+    #[test]
+    fn complex_polar_format() -> Result<(), Box<dyn std::error::Error>> {
+        let format = Format {
+            precision: Some(4),
+            complex: ComplexStyle::Polar,
+            ..Format::default()
+        };
+        assert!(
+            format_value(&eval_in_mode(Mode::Complex, "1 + i")?, &format) == "1.4142*e^(0.7854i)"
+        );
+        assert!(format_value(&eval_in_mode(Mode::Complex, "-2")?, &format) == "2*e^(3.1416i)");
+        assert!(format_value(&eval_in_mode(Mode::Complex, "3")?, &format) == "3");
+        Ok(())
+    }
+    #[test]
+    fn decimal_arithmetic_is_exact() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::Decimal, "0.1 + 0.2")?.to_string() == "0.30");
+        assert!(eval_in_mode(Mode::Decimal, "0.1 + 0.2")? == eval_in_mode(Mode::Decimal, "0.3")?);
+        assert!(eval_in_mode(Mode::Decimal, "19.99 * 3")?.to_string() == "59.97");
+        Ok(())
+    }
+    #[test]
+    fn decimal_results_round_to_scale() -> Result<(), Box<dyn std::error::Error>> {
+        let scaled = |input, scale| {
+            let config = Config {
+                mode: Mode::Decimal,
+                decimal: DecimalConfig {
+                    scale,
+                    rounding: Rounding::HalfEven,
+                },
+                ..Config::default()
+            };
+            evaluate_with(input, &config)
+        };
+        assert!(scaled("2 / 3", 4)?.to_string() == "0.6667");
+        assert!(scaled("-1 / 40", 3)?.to_string() == "-0.025");
+        assert!(scaled("1.5 ^ 2", 1)?.to_string() == "2.2");
+        assert!(scaled("10 / 4", 0)? == 2);
+        Ok(())
+    }
+    #[test]
+    fn decimal_errors() {
+        assert!(
+            eval_in_mode(Mode::Decimal, "1 / (2 - 2)")
+                == Err(Error::Eval(EvalError::DivideByZero, Span::new(2, 3)))
+        );
+        assert!(
+            eval_in_mode(Mode::Decimal, "4 ^ 0.5")
+                == Err(Error::Eval(EvalError::NotAnInteger, Span::new(2, 3)))
+        );
+    }
+    #[test]
+    fn decimal_rounding_modes() -> Result<(), Box<dyn std::error::Error>> {
+        for (input, half_even, half_up, truncate) in [
+            ("0.125", "0.12", "0.13", "0.12"),
+            ("0.135", "0.14", "0.14", "0.13"),
+            ("-0.125", "-0.12", "-0.13", "-0.12"),
+            ("-2 / 3", "-0.67", "-0.67", "-0.66"),
+            ("0.001", "0.00", "0.00", "0.00"),
+        ] {
+            let rounded = |rounding| {
+                let config = Config {
+                    mode: Mode::Decimal,
+                    decimal: DecimalConfig { scale: 2, rounding },
+                    ..Config::default()
+                };
+                evaluate_with(input, &config)
+            };
+            assert!(rounded(Rounding::HalfEven)?.to_string() == half_even);
+            assert!(rounded(Rounding::HalfUp)?.to_string() == half_up);
+            assert!(rounded(Rounding::Truncate)?.to_string() == truncate);
+        }
+        Ok(())
+    }
+    #[test]
+    fn variables_persist_in_environment() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config::default();
+        let mut env = Environment::new();
+        assert!(evaluate_in("rate = 7", &config, &mut env)? == Value::Int(7));
+        assert!(evaluate_in("rate * 12", &config, &mut env)? == Value::Int(84));
+        assert!(evaluate_in("total_2 = rate * (rate - 1)", &config, &mut env)? == Value::Int(42));
+        assert!(evaluate_in("rate = rate + 1", &config, &mut env)? == Value::Int(8));
+        assert!(env.get("rate") == Some(&Value::Int(8)));
+        assert!(env.iter().map(|(name, _)| name).collect::<Vec<_>>() == ["rate", "total_2"]);
+        Ok(())
+    }
+    #[test]
+    fn variable_errors_point_at_the_name() {
+        let config = Config::default();
+        let mut env = Environment::new();
+        assert!(
+            evaluate_in("1 + rat * 2", &config, &mut env)
+                == Err(Error::Eval(EvalError::UndefinedVariable, Span::new(4, 7)))
+        );
+        assert!(
+            evaluate_in("x =", &config, &mut env)
+                == Err(Error::Parse(
+                    ParseError::ExpectedRightOperand,
+                    Span::new(3, 3)
+                ))
+        );
+        assert!(
+            evaluate_in("1 = 2", &config, &mut env)
+                == Err(Error::Parse(ParseError::UnexpectedAssign, Span::new(2, 3)))
+        );
+        assert!(
+            evaluate_in("x = y = 2", &config, &mut env)
+                == Err(Error::Parse(ParseError::UnexpectedAssign, Span::new(6, 7)))
+        );
+        assert!(env.get("x").is_none());
+        assert!(evaluate("x") == Err(Error::Eval(EvalError::UndefinedVariable, Span::new(0, 1))));
+    }
+    #[test]
+    fn builtin_functions() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("sqrt(16) + max(3, 4)")? == 8);
+        assert!(evaluate("min(5, -2, 7) * abs(-3)")? == -6);
+        assert!(evaluate("max(sqrt(81), 2 ^ 3)")? == 9);
+        Ok(())
+    }
+    #[test]
+    fn builtin_functions_follow_the_mode() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(
+            eval_in_mode(Mode::BigInt, "sqrt(10 ^ 40)")?.to_string()
+                == format!("1{}", "0".repeat(20))
+        );
+        assert!(eval_in_mode(Mode::Rational, "sqrt(9 / 4)")?.to_string() == "3/2");
+        assert!(eval_in_mode(Mode::Rational, "floor(-7 / 2) + ceil(7 / 2) + round(5 / 2)")? == 3);
+        assert!(eval_in_mode(Mode::Float, "floor(2.5) + round(-2.5)")? == -1);
+        assert!(eval_in_mode(Mode::Float, "log(1000) + ln(1) + log(8, 2)")? == 6);
+        assert!(eval_in_mode(Mode::Float, "cos(0) - exp(0) + sin(0)")? == 0);
+        assert!(eval_in_mode(Mode::Complex, "sqrt(-4)")?.to_string() == "2i");
+        assert!(eval_in_mode(Mode::Complex, "abs(3 + 4i)")? == 5);
+        assert!(eval_in_mode(Mode::Complex, "max(1, 2)")? == 2);
+        assert!(eval_in_mode(Mode::Decimal, "sqrt(2)")?.to_string() == "1.41");
+        Ok(())
+    }
+    #[test]
+    fn builtin_function_errors() {
+        assert!(evaluate("1 + sqrt(2)") == Err(Error::Eval(EvalError::NotExact, Span::new(4, 11))));
+        assert!(evaluate("sqrt(-4)") == Err(Error::Eval(EvalError::Domain, Span::new(0, 8))));
+        assert!(evaluate("sin(1)") == Err(Error::Eval(EvalError::NotExact, Span::new(0, 6))));
+        assert!(
+            evaluate("2 * nope(1)")
+                == Err(Error::Eval(EvalError::UnknownFunction, Span::new(4, 8)))
+        );
+        let wrong_arity = EvalError::WrongArity {
+            expected: Arity::exact(1),
+            found: 2,
+        };
+        assert!(evaluate("abs(1, 2)") == Err(Error::Eval(wrong_arity.clone(), Span::new(0, 9))));
+        assert!(wrong_arity.to_string() == "expected 1 argument, found 2");
+        assert!(
+            evaluate("max()")
+                == Err(Error::Eval(
+                    EvalError::WrongArity {
+                        expected: Arity::at_least(1),
+                        found: 0,
+                    },
+                    Span::new(0, 5)
+                ))
+        );
+        assert!(
+            eval_in_mode(Mode::Complex, "floor(1 + i)")
+                == Err(Error::Eval(EvalError::NotReal, Span::new(0, 12)))
+        );
+        assert!(
+            evaluate("max(1,)")
+                == Err(Error::Parse(
+                    ParseError::UnexpectedCloseParenth,
+                    Span::new(6, 7)
+                ))
+        );
+        assert!(
+            evaluate("max(1 2)")
+                == Err(Error::Parse(ParseError::ExpectedOperator, Span::new(6, 7)))
+        );
+        assert!(
+            evaluate("max(1, 2")
+                == Err(Error::Parse(
+                    ParseError::ExpectedCloseParenth,
+                    Span::new(8, 8)
+                ))
+        );
+        assert!(
+            evaluate("(1, 2)") == Err(Error::Parse(ParseError::UnexpectedComma, Span::new(2, 3)))
+        );
+        assert!(
+            evaluate("1, 2") == Err(Error::Parse(ParseError::UnexpectedComma, Span::new(1, 2)))
+        );
+    }
+    #[derive(Debug)]
+    struct UnknownId(i64);
+    impl std::fmt::Display for UnknownId {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "no record with id {}", self.0)
+        }
+    }
+    impl std::error::Error for UnknownId {}
+    fn host_context() -> Context {
+        let mut context = Context::new();
+        let rate = 20;
+        context.register("tax", Arity::exact(1), move |args| match &args[0] {
+            Value::Int(amount) => Ok(Value::Int(amount * rate / 100)),
+            _ => Err(EvalError::NotAnInteger.into()),
+        });
+        context.register("lookup", Arity::exact(1), |args| match &args[0] {
+            Value::Int(1) => Ok(Value::Int(250)),
+            Value::Int(id) => Err(UnknownId(*id).into()),
+            _ => Err(EvalError::NotAnInteger.into()),
+        });
+        context
+    }
+    #[test]
+    fn host_functions() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = host_context();
+        assert!(context.evaluate("tax(lookup(1)) + sqrt(4)")? == Value::Int(52));
+        assert!(context.evaluate("price = lookup(1)")? == Value::Int(250));
+        assert!(context.evaluate("price - tax(price)")? == Value::Int(200));
+        context.register("sqrt", Arity::exact(1), |_| Ok(Value::Int(-1)));
+        assert!(context.evaluate("sqrt(4)")? == Value::Int(-1));
+        assert!(context.unregister("sqrt"));
+        assert!(context.evaluate("sqrt(4)")? == Value::Int(2));
+        assert!(context.functions().collect::<Vec<_>>() == ["lookup", "tax"]);
+        let expr = math_parse(&math_lexer("tax(10)")?)?;
+        assert!(context.traverse(&expr)? == 2);
+        Ok(())
+    }
+    #[test]
+    fn host_function_errors() {
+        let mut context = host_context();
+        let error = context.evaluate("1 + lookup(7)").unwrap_err();
+        match &error {
+            Error::Eval(EvalError::Host(host), span) => {
+                assert!(host.downcast_ref::<UnknownId>().is_some_and(|id| id.0 == 7));
+                assert!(*span == Span::new(4, 13));
+            }
+            _ => panic!("expected a host error, got {:?}", error),
+        }
+        assert!(error.to_string() == "no record with id 7");
+        assert!(std::error::Error::source(&error).is_some());
+        context.config.mode = Mode::Float;
+        assert!(
+            context.evaluate("tax(1.5)")
+                == Err(Error::Eval(EvalError::NotAnInteger, Span::new(0, 8)))
+        );
+        assert!(
+            context.evaluate("tax(1, 2)")
+                == Err(Error::Eval(
+                    EvalError::WrongArity {
+                        expected: Arity::exact(1),
+                        found: 2,
+                    },
+                    Span::new(0, 9)
+                ))
+        );
+    }
+    #[test]
+    fn user_defined_functions() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        assert!(context.run("f(x, y) = x^2 + y")?.is_none());
+        assert!(context.evaluate("f(3, 1)")? == Value::Int(10));
+        assert!(context.evaluate("x = 100")? == Value::Int(100));
+        // parameters shadow variables, other names fall back to them
+        assert!(context.evaluate("f(2, 0) + x")? == Value::Int(104));
+        assert!(context.run("scale(n) = n * x")?.is_none());
+        assert!(context.evaluate("scale(f(1, 1))")? == Value::Int(200));
+        assert!(context.run("zero() = 0")?.is_none());
+        assert!(context.evaluate("zero() + 1")? == Value::Int(1));
+        // definitions shadow built-ins
+        assert!(context.run("abs(n) = 7")?.is_none());
+        assert!(context.evaluate("abs(-1)")? == Value::Int(7));
+        assert!(context.env.undefine("abs").is_some());
+        assert!(context.evaluate("abs(-1)")? == Value::Int(1));
+        let names: Vec<String> = context
+            .env
+            .functions()
+            .map(|(name, function)| format!("{}{}", name, function))
+            .collect();
+        assert!(names == ["f(x, y) = x ^ 2 + y", "scale(n) = n * x", "zero() = 0"]);
+        Ok(())
+    }
+    #[test]
+    fn user_defined_function_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        context.run("loop(n) = loop(n + 1)")?;
+        assert!(
+            context.evaluate("1 + loop(0)")
+                == Err(Error::Eval(EvalError::RecursionLimit, Span::new(4, 11)))
+        );
+        context.run("inv(n) = 1 / n")?;
+        assert!(
+            context.evaluate("inv(0)")
+                == Err(Error::Eval(EvalError::DivideByZero, Span::new(0, 6)))
+        );
+        assert!(
+            context.evaluate("g(x, x) = x")
+                == Err(Error::Parse(
+                    ParseError::DuplicateParameter,
+                    Span::new(5, 6)
+                ))
+        );
+        assert!(
+            context.evaluate("g(1) = 2")
+                == Err(Error::Parse(ParseError::UnexpectedAssign, Span::new(5, 6)))
+        );
+        assert!(
+            context.evaluate("g(x) =")
+                == Err(Error::Parse(
+                    ParseError::ExpectedRightOperand,
+                    Span::new(6, 6)
+                ))
+        );
+        assert!(
+            context.evaluate("inv(1, 2)")
+                == Err(Error::Eval(
+                    EvalError::WrongArity {
+                        expected: Arity::exact(1),
+                        found: 2,
+                    },
+                    Span::new(0, 9)
+                ))
+        );
+        context.config.max_call_depth = 0;
+        assert!(
+            context.evaluate("inv(1)")
+                == Err(Error::Eval(EvalError::RecursionLimit, Span::new(0, 6)))
+        );
+        Ok(())
+    }
+    #[test]
+    fn deep_bodies_count_towards_the_recursion_limit() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        let nested = format!("{}f(n - 1){}", "(".repeat(40), ")".repeat(40));
+        context.run(&format!("f(n) = if n <= 0 then 0 else {}", nested))?;
+        assert!(context.evaluate("f(5)")? == Value::Int(0));
+        assert!(
+            context.evaluate("f(99)")
+                == Err(Error::Eval(EvalError::RecursionLimit, Span::new(0, 5)))
+        );
+        context.config.max_call_depth = usize::MAX;
+        assert!(
+            context.evaluate("f(100000)")
+                == Err(Error::Eval(EvalError::RecursionLimit, Span::new(0, 9)))
+        );
+        Ok(())
+    }
+    #[test]
+    fn evaluate_rejects_definitions() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        assert!(
+            context.evaluate("f(x) = x")
+                == Err(Error::Parse(
+                    ParseError::UnexpectedDefinition,
+                    Span::new(0, 1)
+                ))
+        );
+        assert!(context.env.functions().next().is_none());
+        let mut env = Environment::new();
+        assert!(run_in("f(x) = x + 1", &Config::default(), &mut env)?.is_none());
+        assert!(run_in("f(1)", &Config::default(), &mut env)? == Some(Value::Int(2)));
+        assert!(evaluate_in("f(2)", &Config::default(), &mut env)? == 3);
+        Ok(())
+    }
+    #[test]
+    fn calls_may_repeat_an_argument() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        context.evaluate("x = 4")?;
+        assert!(context.evaluate("max(x, x)")? == Value::Int(4));
+        assert!(context.evaluate("min(x,x) + 1")? == Value::Int(5));
+        Ok(())
+    }
+    #[test]
+    fn expressions_print_as_source() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config {
+            mode: Mode::Complex,
+            ..Config::default()
+        };
+        for input in [
+            "-(1 + 2.5) * max(x, 0.05) ^ 2",
+            "25e-9 / 3e4 + 2i",
+            "f() - +x",
+        ] {
+            let expr = math_parse(&math_lexer_with(input, &config)?)?;
+            assert!(expr.to_string() == input);
+        }
+        Ok(())
+    }
+    #[test]
+    fn named_constants() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::Float, "pi")? == Value::Float(std::f64::consts::PI));
+        assert!(eval_in_mode(Mode::Float, "2 * pi - tau")? == Value::Float(0.0));
+        assert!(eval_in_mode(Mode::Float, "ln(e)")? == Value::Float(1.0));
+        assert!(eval_in_mode(Mode::Float, "phi")? == Value::Float(1.618033988749895));
+        assert!(eval_in_mode(Mode::Decimal, "pi")?.to_string() == "3.14");
+        let truncated = Config {
+            mode: Mode::Decimal,
+            decimal: DecimalConfig {
+                scale: 0,
+                rounding: Rounding::Truncate,
+            },
+            ..Config::default()
+        };
+        assert!(evaluate_with("e", &truncated)?.to_string() == "2");
+        Ok(())
+    }
+    #[test]
+    fn named_constants_need_an_inexact_mode() {
+        assert!(evaluate("pi") == Err(Error::Eval(EvalError::NotExact, Span::new(0, 2))));
+        assert!(
+            eval_in_mode(Mode::Rational, "1 + e")
+                == Err(Error::Eval(EvalError::NotExact, Span::new(4, 5)))
+        );
+    }
+    #[test]
+    fn named_constants_cannot_be_assigned() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        assert!(
+            context.evaluate("pi = 3")
+                == Err(Error::Eval(EvalError::ConstantAssignment, Span::new(0, 2)))
+        );
+        context.run("area(pi) = pi * 2")?;
+        assert!(context.evaluate("area(4)")? == Value::Int(8));
+        context.evaluate("g = 1")?;
+        context.env.set_constant("g", Value::Int(9));
+        context.env.set_constant("e", Value::Int(3));
+        assert!(context.evaluate("g + e")? == Value::Int(12));
+        assert!(
+            context.evaluate("g = 2")
+                == Err(Error::Eval(EvalError::ConstantAssignment, Span::new(0, 1)))
+        );
+        Ok(())
+    }
+    #[test]
+    fn remainder_modulo_and_floor_division() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("7 % 3")? == 1);
+        assert!(evaluate("-7 % 3")? == -1);
+        assert!(evaluate("7 % -3")? == 1);
+        assert!(evaluate("-7 mod 3")? == 2);
+        assert!(evaluate("-7 mod -3")? == 2);
+        assert!(evaluate("7 // 2")? == 3);
+        assert!(evaluate("-7 // 2")? == -4);
+        assert!(evaluate("7 // -2")? == -4);
+        assert!(evaluate("(-9223372036854775807 - 1) % -1")? == 0);
+        assert!(evaluate("1 + 10 % 4 * 3")? == 7);
+        let expr = math_parse(&math_lexer("a mod b // 2 % 1")?)?;
+        assert!(expr.to_string() == "a mod b // 2 % 1");
+        Ok(())
+    }
+    #[test]
+    fn remainder_follows_the_mode() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::BigInt, "-7 mod 3")? == Value::Big(BigInt::from(2)));
+        assert!(eval_in_mode(Mode::BigInt, "-7 // 2")? == Value::Big(BigInt::from(-4)));
+        assert!(eval_in_mode(Mode::Rational, "-7/2 % 2")?.to_string() == "-3/2");
+        assert!(eval_in_mode(Mode::Rational, "-7/2 mod 2")?.to_string() == "1/2");
+        assert!(eval_in_mode(Mode::Rational, "-7/2 // 2")? == -2);
+        assert!(eval_in_mode(Mode::Float, "5.5 % 2")? == Value::Float(1.5));
+        assert!(eval_in_mode(Mode::Float, "-5.5 mod 2")? == Value::Float(0.5));
+        assert!(eval_in_mode(Mode::Float, "-5.5 // 2")? == Value::Float(-3.0));
+        assert!(eval_in_mode(Mode::Decimal, "-5.5 mod 2")?.to_string() == "0.50");
+        assert!(
+            eval_in_mode(Mode::Complex, "-5.5 mod 2")? == Value::Complex(Complex::new(0.5, 0.0))
+        );
+        Ok(())
+    }
+    #[test]
+    fn remainder_by_zero_is_an_error() -> Result<(), Box<dyn std::error::Error>> {
+        for (input, span) in [
+            ("7 % 0", Span::new(2, 3)),
+            ("7 mod 0", Span::new(2, 5)),
+            ("7 // 0", Span::new(2, 4)),
+        ] {
+            assert!(evaluate(input) == Err(Error::Eval(EvalError::DivideByZero, span)));
+            assert!(
+                eval_in_mode(Mode::Rational, input)
+                    == Err(Error::Eval(EvalError::DivideByZero, span))
+            );
+            assert!(
+                eval_in_mode(Mode::Complex, input)
+                    == Err(Error::Eval(EvalError::DivideByZero, span))
+            );
+        }
+        assert!(
+            eval_in_mode(Mode::BigInt, "7 mod 0")
+                == Err(Error::Eval(EvalError::DivideByZero, Span::new(2, 5)))
+        );
+        match eval_in_mode(Mode::Float, "7 % 0")? {
+            Value::Float(value) => assert!(value.is_nan()),
+            other => return Err(format!("expected NaN, got {}", other).into()),
+        }
+        assert!(
+            eval_in_mode(Mode::Complex, "i % 2")
+                == Err(Error::Eval(EvalError::NotReal, Span::new(2, 3)))
+        );
+        Ok(())
+    }
+    #[test]
+    fn factorials() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("5!")? == 120);
+        assert!(evaluate("0!")? == 1);
+        assert!(evaluate("7!!")? == 105);
+        assert!(evaluate("8!!")? == 384);
+        assert!(evaluate("2^3!")? == 64);
+        assert!(evaluate("3!^2")? == 36);
+        assert!(evaluate("-3!")? == -6);
+        assert!(evaluate("(3!)!")? == 720);
+        assert!(evaluate("20!")? == 2432902008176640000);
+        let expr = math_parse(&math_lexer("-(2 + 1)! ^ 2!!")?)?;
+        assert!(expr.to_string() == "-(2 + 1)! ^ 2!!");
+        Ok(())
+    }
+    #[test]
+    fn factorials_follow_the_mode() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(eval_in_mode(Mode::BigInt, "25!")?.to_string() == "15511210043330985984000000");
+        assert!(eval_in_mode(Mode::Rational, "(12/4)!")? == 6);
+        assert!(eval_in_mode(Mode::Float, "4!")? == Value::Float(24.0));
+        assert!(eval_in_mode(Mode::Float, "171!")? == Value::Float(f64::INFINITY));
+        assert!(eval_in_mode(Mode::Decimal, "5!!")?.to_string() == "15.00");
+        Ok(())
+    }
+    #[test]
+    fn factorial_errors() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("21!") == Err(Error::Eval(EvalError::Overflow, Span::new(2, 3))));
+        assert!(
+            evaluate("(-3)!") == Err(Error::Eval(EvalError::NegativeFactorial, Span::new(4, 5)))
+        );
+        assert!(
+            eval_in_mode(Mode::Float, "(0-1.5)!!")
+                == Err(Error::Eval(EvalError::NegativeFactorial, Span::new(7, 9)))
+        );
+        assert!(
+            eval_in_mode(Mode::Float, "2.5!")
+                == Err(Error::Eval(EvalError::NotAnInteger, Span::new(3, 4)))
+        );
+        assert!(
+            eval_in_mode(Mode::BigInt, "100000000!")
+                == Err(Error::Eval(EvalError::Overflow, Span::new(9, 10)))
+        );
+        assert!(evaluate("!3") == Err(Error::Eval(EvalError::ExpectedBool, Span::new(0, 1))));
+        let wrapping = Config {
+            overflow: Overflow::Wrapping,
+            ..Config::default()
+        };
+        assert!(evaluate_with("100!", &wrapping)? == 0);
+        Ok(())
+    }
+    #[test]
+    fn bitwise_operators() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("0xFF & 0b1010")? == 10);
+        assert!(evaluate("0xF0 | 0o17")? == 255);
+        assert!(evaluate("6 ^^ 3")? == 5);
+        assert!(evaluate("~0")? == -1);
+        assert!(evaluate("-6 & 0xF")? == 10);
+        assert!(evaluate("1 << 2 + 1")? == 8);
+        assert!(evaluate("1 | 2 ^^ 3 & 6")? == 1);
+        assert!(evaluate("-9 >> 1")? == -5);
+        assert!(evaluate("-1 >> 100")? == -1);
+        assert!(evaluate("-1 << 63")? == i64::MIN);
+        assert!(evaluate("1 << 63") == Err(Error::Eval(EvalError::Overflow, Span::new(2, 4))));
+        assert!(evaluate("1 << -1") == Err(Error::Eval(EvalError::NegativeShift, Span::new(2, 4))));
+        assert!(eval_in_mode(Mode::BigInt, "1 << 64")?.to_string() == "18446744073709551616");
+        assert!(eval_in_mode(Mode::BigInt, "-(1 << 70) >> 68")? == -4);
+        assert!(
+            eval_in_mode(Mode::BigInt, "~(1 << 64) & (3 << 63)")?.to_string()
+                == "9223372036854775808"
+        );
+        assert!(eval_in_mode(Mode::Float, "0xF0 & 0x3C")? == Value::Float(48.0));
+        assert!(eval_in_mode(Mode::Rational, "~(6/2)")? == -4);
+        assert!(
+            eval_in_mode(Mode::Float, "1.5 | 1")
+                == Err(Error::Eval(EvalError::NotAnInteger, Span::new(4, 5)))
+        );
+        Ok(())
+    }
+    #[test]
+    fn radix_literals_and_digit_separators() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("1_000_000")? == 1000000);
+        assert!(evaluate("0xFFFF_0000")? == 0xFFFF_0000);
+        assert!(eval_in_mode(Mode::Float, "1_000.000_5")? == Value::Float(1000.0005));
+        assert!(
+            eval_in_mode(Mode::BigInt, "0x1_0000_0000_0000_0000")?.to_string()
+                == "18446744073709551616"
+        );
+        for (input, span) in [
+            ("0x", Span::new(0, 2)),
+            ("0b102", Span::new(0, 5)),
+            ("0xFG", Span::new(0, 4)),
+            ("1__0", Span::new(0, 4)),
+            ("1_ + 2", Span::new(0, 2)),
+        ] {
+            assert!(evaluate(input) == Err(Error::Lex(LexError::MalformedNumber, span)));
+        }
+        let hex = Format {
+            radix: Radix::Hex,
+            ..Format::default()
+        };
+        assert!(format_value(&evaluate("255")?, &hex) == "0xFF");
+        assert!(format_value(&evaluate("-16")?, &hex) == "-0x10");
+        assert!(
+            format_value(&eval_in_mode(Mode::BigInt, "1 << 64")?, &hex) == "0x10000000000000000"
+        );
+        let binary = Format {
+            radix: Radix::Binary,
+            ..Format::default()
+        };
+        assert!(format_value(&evaluate("10")?, &binary) == "0b1010");
+        let octal = Format {
+            radix: Radix::Octal,
+            ..Format::default()
+        };
+        assert!(format_value(&evaluate("0")?, &octal) == "0o0");
+        assert!(format_value(&eval_in_mode(Mode::Float, "2.5")?, &octal) == "2.5");
+        Ok(())
+    }
+    #[test]
+    fn comparisons_and_logic() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        context.evaluate("x = 5")?;
+        context.evaluate("y = 10")?;
+        assert!(context.evaluate("x > 3 && y <= 10")? == Value::Bool(true));
+        assert!(context.evaluate("x == 5 || undefined > 1")? == Value::Bool(true));
+        assert!(context.evaluate("x != 5 && undefined > 1")? == Value::Bool(false));
+        assert!(context.evaluate("!(x < 3)")? == Value::Bool(true));
+        assert!(context.evaluate("!!true")? == Value::Bool(true));
+        assert!(context.evaluate("1 + 1 == 2 == true")? == Value::Bool(true));
+        assert!(context.evaluate("ok = x >= y")? == Value::Bool(false));
+        assert!(context.evaluate("false || ok")? == Value::Bool(false));
+        assert!(evaluate("5! == 120")? == Value::Bool(true));
+        assert!(evaluate("5!=120")? == Value::Bool(true));
+        assert!(evaluate("1 << 2 < 5")? == Value::Bool(true));
+        assert!(eval_in_mode(Mode::Rational, "1/3 < 0.34")? == Value::Bool(true));
+        assert!(eval_in_mode(Mode::Float, "0/0 == 0/0")? == Value::Bool(false));
+        assert!(eval_in_mode(Mode::Float, "0/0 != 0/0")? == Value::Bool(true));
+        assert!(eval_in_mode(Mode::Complex, "i == 1i")? == Value::Bool(true));
+        assert!(evaluate("true")?.to_string() == "true");
+        let expr = math_parse(&math_lexer("!(a < b) || c >= 1 && !!d")?)?;
+        assert!(expr.to_string() == "!(a < b) || c >= 1 && !!d");
+        Ok(())
+    }
+    #[test]
+    fn booleans_and_numbers_do_not_mix() -> Result<(), Box<dyn std::error::Error>> {
+        let expected_number = |start, end| {
+            Err(Error::Eval(
+                EvalError::ExpectedNumber,
+                Span::new(start, end),
+            ))
+        };
+        let expected_bool =
+            |start, end| Err(Error::Eval(EvalError::ExpectedBool, Span::new(start, end)));
+        assert!(evaluate("(1 < 2) + 1") == expected_number(8, 9));
+        assert!(evaluate("-true") == expected_number(0, 1));
+        assert!(evaluate("true < false") == expected_number(5, 6));
+        assert!(evaluate("true == 1") == expected_number(5, 7));
+        assert!(evaluate("sqrt(true)") == expected_number(0, 10));
+        assert!(evaluate("1 && true") == expected_bool(2, 4));
+        assert!(evaluate("true || 1") == Ok(Value::Bool(true)));
+        assert!(evaluate("false || 1") == expected_bool(6, 8));
+        assert!(evaluate("!1") == expected_bool(0, 1));
+        assert!(
+            eval_in_mode(Mode::Complex, "i < 1")
+                == Err(Error::Eval(EvalError::NotReal, Span::new(2, 3)))
+        );
+        Ok(())
+    }
+    #[test]
+    fn conditional_expressions() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        context.evaluate("price = 20")?;
+        context.evaluate("qty = 150")?;
+        context.run("total(q) = q * (if q > 100 then price * 9 / 10 else price)")?;
+        assert!(context.evaluate("total(qty)")? == Value::Int(2700));
+        assert!(context.evaluate("total(10)")? == Value::Int(200));
+        assert!(context.evaluate("qty > 100 ? 1 : 2")? == Value::Int(1));
+        assert!(context.evaluate("1 + (qty < 100 ? 1 : 2)")? == Value::Int(3));
+        assert!(context.evaluate("if true then 1 else 2 + 3")? == Value::Int(1));
+        assert!(context.evaluate("if false then 1 else 2 + 3")? == Value::Int(5));
+        assert!(context.evaluate("false ? 1 : true ? 2 : 3")? == Value::Int(2));
+        assert!(context.evaluate("true ? false ? 1 : 2 : 3")? == Value::Int(2));
+        // the untaken branch is never evaluated
+        assert!(context.evaluate("qty > 0 ? 1 : 1 / 0")? == Value::Int(1));
+        assert!(context.evaluate("if qty == 0 then 1 / 0 else 7")? == Value::Int(7));
+        context.run("fact(n) = if n <= 1 then 1 else n * fact(n - 1)")?;
+        assert!(context.evaluate("fact(10)")? == Value::Int(3628800));
+        for (input, printed) in [
+            ("a ? b : c ? d : e", "if a then b else if c then d else e"),
+            (
+                "1 + (if a then b else c) * 2",
+                "1 + (if a then b else c) * 2",
+            ),
+        ] {
+            let expr = math_parse(&math_lexer(input)?)?;
+            assert!(expr.to_string() == printed);
+            assert!(math_parse(&math_lexer(&expr.to_string())?)?.to_string() == printed);
+        }
+        Ok(())
+    }
+    #[test]
+    fn conditional_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let parse_error = |input: &str, kind, start, end| {
+            evaluate(input) == Err(Error::Parse(kind, Span::new(start, end)))
+        };
+        assert!(parse_error(
+            "if 1 < 2 else 3",
+            ParseError::ExpectedThen,
+            9,
+            13
+        ));
+        assert!(parse_error(
+            "if true then 1",
+            ParseError::ExpectedElse,
+            14,
+            14
+        ));
+        assert!(parse_error("true ? 1", ParseError::ExpectedColon, 8, 8));
+        assert!(parse_error("1 else 2", ParseError::UnmatchedBranch, 2, 6));
+        assert!(parse_error("1 : 2", ParseError::UnmatchedBranch, 2, 3));
+        assert!(parse_error(
+            "if true then else 2",
+            ParseError::ExpectedRightOperand,
+            13,
+            17
+        ));
+        assert!(
+            evaluate("if 1 then 2 else 3")
+                == Err(Error::Eval(EvalError::ExpectedBool, Span::new(3, 4)))
+        );
+        Ok(())
+    }
+    #[test]
+    fn implicit_multiplication() -> Result<(), Box<dyn std::error::Error>> {
+        let mut context = Context::new();
+        context.evaluate("x = 3")?;
+        context.evaluate("y = 4")?;
+        for (input, expected) in [
+            ("2x", 6),
+            ("3(4 + 5)", 27),
+            ("(x)(y)", 12),
+            ("(1 + 1)x", 6),
+            ("x y", 12),
+            ("2x^2", 18),
+            ("-2x", -6),
+            ("2(3)!", 12),
+            ("1 + 2x", 7),
+            ("6/2(1+2)", 9),
+            ("2x / 3y", 8),
+        ] {
+            assert!(context.evaluate(input)? == Value::Int(expected));
+        }
+        context.config.implicit_multiplication = ImplicitMultiplication::Tight;
+        for (input, expected) in [
+            ("6/2(1+2)", 1),
+            ("12 / 2x", 2),
+            ("2x^2", 18),
+            ("2^2x", 12),
+            ("-2x", -6),
+            ("x y - 2(y)", 4),
+        ] {
+            assert!(context.evaluate(input)? == Value::Int(expected));
+        }
+        // precedence alone makes the product the divisor
+        let expr = math_parse_with(&math_lexer("6/2(1+2)")?, &context.config)?;
+        let Expr::Binary {
+            op: Ops::Divide,
+            rhs,
+            ..
+        } = &expr
+        else {
+            panic!("expected a division");
+        };
+        assert!(matches!(
+            **rhs,
+            Expr::Binary {
+                op: Ops::Multiply,
+                span: Span { start: 3, end: 3 },
+                ..
+            }
+        ));
+        // and printing keeps it the divisor in every mode
+        for (input, printed) in [("6/2x", "6 / (2 * x)"), ("2x / 3y", "2 * x / (3 * y)")] {
+            let expr = math_parse_with(&math_lexer(input)?, &context.config)?;
+            assert!(expr.to_string() == printed);
+            let value = context.evaluate(input)?;
+            context.config.implicit_multiplication = ImplicitMultiplication::AsMultiply;
+            assert!(context.evaluate(printed)? == value);
+            context.config.implicit_multiplication = ImplicitMultiplication::Tight;
+        }
+        Ok(())
+    }
+    #[test]
+    fn implicit_multiplication_errors() {
+        assert!(
+            evaluate("2 3") == Err(Error::Parse(ParseError::ExpectedOperator, Span::new(2, 3)))
+        );
+        assert!(
+            evaluate("(2)3") == Err(Error::Parse(ParseError::ExpectedOperator, Span::new(3, 4)))
+        );
+        // a name directly before a parenthesis is still a call
+        assert!(evaluate("x(3)") == Err(Error::Eval(EvalError::UnknownFunction, Span::new(0, 1))));
+        assert!(
+            evaluate("2 (1 / 0)") == Err(Error::Eval(EvalError::DivideByZero, Span::new(5, 6)))
+        );
+        assert!(
+            evaluate("9223372036854775807(2)")
+                == Err(Error::Eval(EvalError::Overflow, Span::new(19, 19)))
+        );
+        let config = Config {
+            implicit_multiplication: ImplicitMultiplication::Disabled,
+            ..Config::default()
+        };
+        assert!(
+            evaluate_with("2(3)", &config)
+                == Err(Error::Parse(ParseError::ExpectedOperator, Span::new(1, 2)))
+        );
+    }
+    #[test]
+    fn unicode_operators() -> Result<(), Box<dyn std::error::Error>> {
+        for (input, expected) in [
+            ("6 × 7", 42),
+            ("2·3⋅4", 24),
+            ("84 ÷ 2", 42),
+            ("50 − 8", 42),
+            ("√16 + 2", 6),
+            ("2√(3 × 3)", 6),
+            ("√3²", 3),
+            ("5²", 25),
+            ("2¹⁰", 1024),
+            ("(1 + 2)³", 27),
+            ("-3²", -9),
+            ("2x²", 0),
+        ] {
+            let mut context = Context::new();
+            context.evaluate("x = 0")?;
+            assert!(context.evaluate(input)? == Value::Int(expected));
+        }
+        assert!(evaluate("3 ≠ 4 && 3 ≤ 3 && 4 ≥ 5") == Ok(Value::Bool(false)));
+        assert!(evaluate("1\u{a0}+\u{2003}1")? == 2);
+        assert!(eval_in_mode(Mode::Float, "2π")? == eval_in_mode(Mode::Float, "2 * pi")?);
+        assert!(eval_in_mode(Mode::Float, "4⁻¹")? == Value::Float(0.25));
+        assert!(eval_in_mode(Mode::Float, "√2 × √2")? == Value::Float(2.0000000000000004));
+        let tokens = math_lexer("x⁻¹²")?;
+        assert!(tokens.len() == 4);
+        assert!(tokens[1].span == Span::new(1, 8));
+        assert!(tokens[2].span == Span::new(1, 4));
+        assert!(tokens[3].span == Span::new(4, 8));
+        assert!(math_parse(&tokens)?.to_string() == "x ^ -12");
+        Ok(())
+    }
+    #[test]
+    fn unicode_errors() {
+        assert!(evaluate("√-4") == Err(Error::Eval(EvalError::Domain, Span::new(0, 3))));
+        assert!(evaluate("√2") == Err(Error::Eval(EvalError::NotExact, Span::new(0, 3))));
+        assert!(evaluate("3 ∞ 4") == Err(Error::Lex(LexError::UnknownToken, Span::new(2, 5))));
+        assert!(evaluate("2⁻") == Err(Error::Lex(LexError::MalformedNumber, Span::new(1, 4))));
+        assert!(evaluate("2²⁻¹") == Err(Error::Lex(LexError::MalformedNumber, Span::new(1, 8))));
+        assert!(evaluate("2 ^ 10⁹⁹") == Err(Error::Eval(EvalError::Overflow, Span::new(6, 12))));
+        let config = Config {
+            strict_ascii: true,
+            ..Config::default()
+        };
+        assert!(
+            evaluate_with("6 × 7", &config)
+                == Err(Error::Lex(LexError::UnknownToken, Span::new(2, 4)))
+        );
+        assert!(
+            evaluate_with("1\u{a0}+ 1", &config)
+                == Err(Error::Lex(LexError::UnknownToken, Span::new(1, 3)))
+        );
+        assert!(evaluate_with("6 * 7", &config) == Ok(Value::Int(42)));
+    }
+    #[test]
+    fn percent_literals() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("200 + 15%")? == 230);
+        assert!(evaluate("200 - 15%")? == 170);
+        assert!(evaluate("100 + 10% + 10%")? == 121);
+        assert!(evaluate("200 + 15% - 10")? == 220);
+        assert!(evaluate("200 + 15% + 5")? == 235);
+        assert!(eval_in_mode(Mode::Float, "if true then 50% else 1")? == Value::Float(0.5));
+        assert!(evaluate("199 + 15%")? == 228);
+        assert!(evaluate("300%")? == 3);
+        assert!(eval_in_mode(Mode::Float, "200 * 15%")? == Value::Float(30.0));
+        assert!(eval_in_mode(Mode::Float, "80 / 50%")? == Value::Float(160.0));
+        assert!(eval_in_mode(Mode::Float, "12.5%")? == Value::Float(0.125));
+        assert!(eval_in_mode(Mode::Float, "-(50 + 20%)")? == Value::Float(-60.0));
+        assert!(eval_in_mode(Mode::Rational, "10 + 5%")?.to_string() == "21/2");
+        assert!(eval_in_mode(Mode::Rational, "1 - 1/3 - 50%")?.to_string() == "1/3");
+        let config = Config {
+            mode: Mode::Float,
+            percent: Percent::Fraction,
+            ..Config::default()
+        };
+        assert!(evaluate_with("200 + 15%", &config)? == Value::Float(200.15));
+        assert!(evaluate_with("200 * 15%", &config)? == Value::Float(30.0));
+        let expr = math_parse(&math_lexer("200 + 15%")?)?;
+        assert!(expr.to_string() == "200 + 15%");
+        Ok(())
+    }
+    #[test]
+    fn percent_sign_before_an_operand_is_a_remainder() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(evaluate("7%3")? == 1);
+        assert!(evaluate("7 % 3")? == 1);
+        assert!(evaluate("7% 3")? == 1);
+        assert!(evaluate("7%(2)")? == 1);
+        assert!(evaluate("200 + 15% - 10")? == 220);
+        assert!(evaluate("7%~1")? == 1);
+        assert!(evaluate("7% √4")? == 1);
+        assert!(evaluate("7 % -3")? == 1);
+        Ok(())
+    }
+    #[test]
+    fn percent_errors() {
+        assert!(evaluate("15%") == Err(Error::Eval(EvalError::NotAnInteger, Span::new(0, 3))));
+        assert!(
+            evaluate("200 * 15%") == Err(Error::Eval(EvalError::NotAnInteger, Span::new(6, 9)))
+        );
+        assert!(
+            evaluate("true + 15%") == Err(Error::Eval(EvalError::ExpectedNumber, Span::new(7, 10)))
+        );
+        assert!(
+            evaluate("x%")
+                == Err(Error::Parse(
+                    ParseError::ExpectedRightOperand,
+                    Span::new(2, 2)
+                ))
+        );
+        assert!(
+            evaluate("2 15%") == Err(Error::Parse(ParseError::ExpectedOperator, Span::new(2, 5)))
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+
+use crate::bigint::{BigInt, MAX_DIGITS};
+use crate::error::EvalError;
+use crate::rational::Rational;
+
+/// A numeric literal exactly as written, `digits * 10^exponent`, so every
+/// evaluation mode can convert it without going through another number type
+/// first. `2.50e1` is stored as digits 250 and exponent -1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Literal {
+    pub digits: BigInt,
+    pub exponent: i64,
+}
+impl Literal {
+    /// The literal as an integer, or `EvalError::NotAnInteger` if it has a
+    /// fractional part.
+    pub fn to_integer(&self) -> Result<BigInt, EvalError> {
+        if self.digits.is_zero() {
+            return Ok(BigInt::zero());
+        }
+        if self.exponent >= 0 {
+            if self.digits.log10_abs() + self.exponent as f64 > MAX_DIGITS {
+                return Err(EvalError::Overflow);
+            }
+            let scale = BigInt::from(10).pow(self.exponent as u32);
+            return Ok(&self.digits * &scale);
+        }
+        // more fractional digits than digits overall means a nonzero fraction
+        if -self.exponent as f64 > self.digits.log10_abs() + 1.0 {
+            return Err(EvalError::NotAnInteger);
+        }
+        let scale = BigInt::from(10).pow(-self.exponent as u32);
+        match self.digits.div_rem(&scale) {
+            Some((quotient, remainder)) if remainder.is_zero() => Ok(quotient),
+            _ => Err(EvalError::NotAnInteger),
+        }
+    }
+    pub fn to_rational(&self) -> Result<Rational, EvalError> {
+        if self.exponent >= 0 {
+            return self.to_integer().map(Rational::from);
+        }
+        if -self.exponent as f64 > MAX_DIGITS {
+            return Err(EvalError::Overflow);
+        }
+        let scale = BigInt::from(10).pow(-self.exponent as u32);
+        Ok(Rational::new(self.digits.clone(), scale).expect("nonzero scale"))
+    }
+    /// The exact value of a finite `f64`, written with as few digits as
+    /// still read back as the same float.
+    pub(crate) fn from_f64(value: f64) -> Option<Literal> {
+        if !value.is_finite() {
+            return None;
+        }
+        let text = format!("{:e}", value.abs());
+        let (mantissa, exponent) = text.split_once('e')?;
+        let (whole, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = BigInt::parse_digits(&format!("{}{}", whole, frac))?;
+        Some(Literal {
+            digits: if value < 0.0 { -&digits } else { digits },
+            exponent: exponent.parse::<i64>().ok()? - frac.len() as i64,
+        })
+    }
+    /// The nearest `f64`, correctly rounded.
+    pub fn to_f64(&self) -> f64 {
+        format!("{}e{}", self.digits, self.exponent)
+            .parse()
+            .unwrap_or(f64::NAN)
+    }
+}
+/// Prints the literal so it lexes back to the same value: `2.5`, `0.05`, or
+/// `25e-9` when that is shorter.
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.digits.to_string();
+        if self.exponent == 0 {
+            return f.write_str(&digits);
+        }
+        let places = self.exponent.unsigned_abs() as usize;
+        if self.exponent > 0 || places > digits.len() + 3 {
+            return write!(f, "{}e{}", digits, self.exponent);
+        }
+        let padded = format!("{:0>width$}", digits, width = places + 1);
+        let (whole, frac) = padded.split_at(padded.len() - places);
+        write!(f, "{}.{}", whole, frac)
+    }
+}
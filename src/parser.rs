@@ -0,0 +1,607 @@
+use crate::ast::{Expr, Statement, UnaryOp};
+use crate::config::{Associativity, Config, ImplicitMultiplication};
+use crate::error::{Error, ParseError, Span};
+use crate::lexer::{Ops, Token, TokenKind};
+
+/// Follows C below `+` and `-`: `||` binds loosest, then `&&`, `|`, `^^`,
+/// `&`, the equality operators, the relational operators and the shifts.
+pub fn get_precedence(op: &Ops) -> u8 {
+    match op {
+        Ops::Or => 1,
+        Ops::And => 2,
+        Ops::BitOr => 3,
+        Ops::BitXor => 4,
+        Ops::BitAnd => 5,
+        Ops::Equal => 6,
+        Ops::NotEqual => 6,
+        Ops::Less => 7,
+        Ops::LessEqual => 7,
+        Ops::Greater => 7,
+        Ops::GreaterEqual => 7,
+        Ops::ShiftLeft => 8,
+        Ops::ShiftRight => 8,
+        Ops::Add => 9,
+        Ops::Subtract => 9,
+        Ops::Multiply => 10,
+        Ops::Divide => 10,
+        Ops::Remainder => 10,
+        Ops::Modulo => 10,
+        Ops::FloorDivide => 10,
+        Ops::Exponent => 11,
+    }
+}
+pub fn get_associativity(op: &Ops) -> Associativity {
+    match op {
+        Ops::Exponent => Associativity::Right,
+        Ops::Add
+        | Ops::Subtract
+        | Ops::Multiply
+        | Ops::Divide
+        | Ops::Remainder
+        | Ops::Modulo
+        | Ops::FloorDivide
+        | Ops::BitAnd
+        | Ops::BitOr
+        | Ops::BitXor
+        | Ops::ShiftLeft
+        | Ops::ShiftRight
+        | Ops::Equal
+        | Ops::NotEqual
+        | Ops::Less
+        | Ops::LessEqual
+        | Ops::Greater
+        | Ops::GreaterEqual
+        | Ops::And
+        | Ops::Or => Associativity::Left,
+    }
+}
+/*
+ *
+ * Old grammar:
+ * BINARYEXPR -> OPENPARENTH BINARYEXPR CLOSEPARENTH | BINARYEXPR OPERATOR BINARYEXPR | NUMBER
+ * OPERATOR -> * | - | ^ | /
+   Some more advice
+   - Expr could be an enum
+   - manual Clone impls could be derived
+   - 'x' as u8 == b'x'
+   - if-else if chains can be match blocks
+   - math_lexer should take &[u8] for the input
+   - you should really assign math_expr_bytes[idx] to a variable since you write it tens of times
+   - as char can be char::from
+   - your grammar definition is left recursive
+   - to be honest I didn't read the parser too much. it seems really complicated
+   - traverse_expr_tree looks fine except for the fact that your definition of Expr makes it inelegant
+   - your print method could be replaced with a Debug impl (derived) on Token
+   - you terminate the whole repl when an expr fails to parse. I think it would be better to print an error but continue reading.
+   - on the other hand, just unwrap the IO results. no need to wory about them and breaking from the loop is kinda confusing honestly especially since you don't print the error
+
+   Some advice I got from discord on defining grammar
+       expr0 = expr1 (binary_operator expr1)*
+       expr1 = unary_operator* expr2
+       expr2 = OPEN_PAREN expr0 CLOSE_PAREN | NUMBER
+       // define unary_ and binary_operator as necessary
+
+
+ * OLD
+   New grammar:
+   uses Augmented Backus Naur Form
+   EXPR -> NUMBER OPERATOR EXPR | OPENPARENTH EXPR CLOSEPARENTH *(OPERATOR EXPR) | NUMBER
+   OPERATOR -> + | - | * | / | ^
+   NUMBER -> INTEGER THAT CAN FIT INTO i64
+   THIS GRAMMAR IS BAD BC IT NEVER CONSIDERED SIGNED NUMBERS (EX: -1 or +1)
+
+**/
+/*
+  Pratt grammar:
+   statement -> ident = expr | ident open_parenth params? close_parenth = expr | expr
+   params  -> ident (, ident)*
+   expr    -> prefix (infix_op expr | postfix_op | ? expr : expr | implicit)*
+   implicit -> prefix starting with ident, open_parenth or √
+   prefix  -> unary_op prefix | if expr then expr else expr | primary
+   primary -> number | imaginary | percent | bool | call | ident | open_parenth expr close_parenth
+   call    -> ident open_parenth (expr (, expr)*)? close_parenth
+   infix_op -> + | - | * | / | % | mod | // | ^ | & | ^^ | '|' | << | >>
+             | == | != | < | <= | > | >= | && | '||'
+   unary_op -> - | + | ~ | ! | !! | √
+   postfix_op -> ! | !!
+
+  Each infix operator gets a (left, right) binding power pair derived from
+  get_precedence; the side with the higher power follows the operator's
+  associativity, so ^ folds right by default. Unary operators bind tighter than * and / but looser than ^,
+  so -2^2 is -(2^2). A ! or !! in front of an operand is logical not (twice
+  for !!), and after one a factorial. Postfix operators bind tighter than ^, so 2^3! is 2^(3!)
+  and -3! is -(3!).
+
+  Juxtaposition multiplies when the right operand starts with a name or a
+  parenthesis, as in 2x, 3(4 + 5) and (a)(b); 2 3 is still an error and x(3)
+  is still a call. Config::implicit_multiplication decides whether it binds
+  like * (6/2(1+2) is 9) or tighter than * and / but looser than ^
+  (6/2(1+2) is 1). Either way 2x^2 is 2(x^2) and -2x is (-2)x.
+
+  A percent literal is a number directly followed by %, as in 15%, as long
+  as nothing that can only start an operand follows the %, even after
+  spaces; 7%3 and 7 % (2) are remainders, but 15% - 10 subtracts. Its
+  meaning, including the calculator reading of a + b%, is left to the
+  evaluator and Config::percent.
+
+  Conditionals come in two spellings. The ternary c ? a : b binds loosest of
+  all and folds right, so a ? b : c ? d : e is a ? b : (c ? d : e). The
+  keyword form is a prefix whose else branch reaches as far right as an
+  expression can, so if c then 1 else 2 + 3 takes 2 + 3 as its else branch.
+  Only the branch the condition picks is evaluated.
+
+  Every nested parse_expr call counts towards MAX_NESTING, so a thousand
+  ( or - is a parse error rather than a stack overflow here or in eval.
+  A flat chain such as 1 + 1 + ... + 1 is built in the loop instead and
+  nests as deep as it is long; eval, Display, Clone and Drop follow such
+  chains with Expr::chain rather than recursing, so it has no limit.
+*/
+fn infix_binding_power(op: &Ops, assoc: Associativity) -> (u8, u8) {
+    let bp = get_precedence(op) * 2;
+    match assoc {
+        Associativity::Left => (bp, bp + 1),
+        Associativity::Right => (bp + 1, bp),
+    }
+}
+/// `None` when implicit multiplication is disabled. The tight powers sit
+/// between `/`'s right power and the prefix power, so `6/2x` takes `2x` as
+/// the divisor while `-2x` still negates only the `2`.
+fn implicit_binding_power(config: &Config) -> Option<(u8, u8)> {
+    match config.implicit_multiplication {
+        ImplicitMultiplication::AsMultiply => Some(infix_binding_power(
+            &Ops::Multiply,
+            config.associativity(&Ops::Multiply),
+        )),
+        ImplicitMultiplication::Tight => Some((prefix_binding_power() - 1, prefix_binding_power())),
+        ImplicitMultiplication::Disabled => None,
+    }
+}
+/// Below every infix operator, whose powers start at 2.
+fn ternary_binding_power() -> u8 {
+    1
+}
+fn prefix_binding_power() -> u8 {
+    get_precedence(&Ops::Exponent) * 2
+}
+fn postfix_binding_power() -> u8 {
+    (get_precedence(&Ops::Exponent) + 1) * 2
+}
+/// How deeply parentheses, prefix operators, right-associative operators
+/// and conditionals may nest before parsing fails with
+/// `ParseError::NestingLimit`. Flat chains of operators such as
+/// `1 + 1 + ... + 1` do not count, as nothing recurses through them.
+pub const MAX_NESTING: usize = 128;
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    config: &'a Config,
+    current: usize,
+    /// How many `parse_expr` calls enclose the current one.
+    depth: usize,
+}
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.current)
+    }
+    /// Zero-width span just past the last token, used for "ran out of input" errors.
+    fn end_span(&self) -> Span {
+        let end = self.tokens.last().map_or(0, |token| token.span.end);
+        Span::new(end, end)
+    }
+    fn parse_expr(&mut self, min_bp: u8) -> Result<Expr, Error> {
+        if self.depth == MAX_NESTING {
+            let span = self
+                .peek()
+                .map_or_else(|| self.end_span(), |token| token.span);
+            return Err(Error::Parse(ParseError::NestingLimit, span));
+        }
+        self.depth += 1;
+        let expr = self.parse_operations(min_bp);
+        self.depth -= 1;
+        expr
+    }
+    fn parse_operations(&mut self, min_bp: u8) -> Result<Expr, Error> {
+        let mut lhs = self.parse_prefix()?;
+        while let Some(token) = self.peek() {
+            let op = match &token.kind {
+                TokenKind::Operator(op) => *op,
+                TokenKind::Bang | TokenKind::DoubleBang => {
+                    if postfix_binding_power() < min_bp {
+                        break;
+                    }
+                    let op = match token.kind {
+                        TokenKind::Bang => UnaryOp::Factorial,
+                        _ => UnaryOp::DoubleFactorial,
+                    };
+                    let span = token.span;
+                    self.current += 1;
+                    lhs = Expr::Unary {
+                        op,
+                        operand: Box::new(lhs),
+                        span,
+                    };
+                    continue;
+                }
+                TokenKind::Question => {
+                    if ternary_binding_power() < min_bp {
+                        break;
+                    }
+                    let span = token.span;
+                    self.current += 1;
+                    let then_branch = self.parse_expr(0)?;
+                    self.expect(&TokenKind::Colon, ParseError::ExpectedColon)?;
+                    let else_branch = self.parse_expr(ternary_binding_power())?;
+                    lhs = Expr::Conditional {
+                        condition: Box::new(lhs),
+                        then_branch: Box::new(then_branch),
+                        else_branch: Box::new(else_branch),
+                        span,
+                    };
+                    continue;
+                }
+                TokenKind::CloseParenth
+                | TokenKind::Comma
+                | TokenKind::Then
+                | TokenKind::Else
+                | TokenKind::Colon => break,
+                TokenKind::Assign => {
+                    return Err(Error::Parse(ParseError::UnexpectedAssign, token.span))
+                }
+                TokenKind::Ident(_) | TokenKind::OpenParenth | TokenKind::Root => {
+                    let Some((left_bp, right_bp)) = implicit_binding_power(self.config) else {
+                        return Err(Error::Parse(ParseError::ExpectedOperator, token.span));
+                    };
+                    if left_bp < min_bp {
+                        break;
+                    }
+                    let span = Span::new(self.tokens[self.current - 1].span.end, token.span.start);
+                    let rhs = self.parse_expr(right_bp)?;
+                    lhs = Expr::Binary {
+                        op: Ops::Multiply,
+                        lhs: Box::new(lhs),
+                        rhs: Box::new(rhs),
+                        span,
+                    };
+                    continue;
+                }
+                TokenKind::Number(_)
+                | TokenKind::Imaginary(_)
+                | TokenKind::Percent(_)
+                | TokenKind::Bool(_)
+                | TokenKind::Tilde
+                | TokenKind::If => {
+                    return Err(Error::Parse(ParseError::ExpectedOperator, token.span))
+                }
+            };
+            let (left_bp, right_bp) = infix_binding_power(&op, self.config.associativity(&op));
+            if left_bp < min_bp {
+                break;
+            }
+            let span = token.span;
+            self.current += 1;
+            let rhs = self.parse_expr(right_bp)?;
+            lhs = Expr::Binary {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                span,
+            };
+        }
+        Ok(lhs)
+    }
+    fn parse_prefix(&mut self) -> Result<Expr, Error> {
+        let token = match self.peek() {
+            Some(token) => token,
+            None => {
+                return Err(Error::Parse(
+                    ParseError::ExpectedRightOperand,
+                    self.end_span(),
+                ))
+            }
+        };
+        let span = token.span;
+        match &token.kind {
+            TokenKind::Number(num) => {
+                let value = num.clone();
+                self.current += 1;
+                Ok(Expr::Number { value, span })
+            }
+            TokenKind::Imaginary(num) => {
+                let value = num.clone();
+                self.current += 1;
+                Ok(Expr::Imaginary { value, span })
+            }
+            TokenKind::Percent(num) => {
+                let value = num.clone();
+                self.current += 1;
+                Ok(Expr::Percent { value, span })
+            }
+            TokenKind::Bool(value) => {
+                let value = *value;
+                self.current += 1;
+                Ok(Expr::Bool { value, span })
+            }
+            TokenKind::Ident(name) => {
+                let name = name.clone();
+                self.current += 1;
+                match self.peek() {
+                    Some(Token {
+                        kind: TokenKind::OpenParenth,
+                        ..
+                    }) => self.parse_call(name, span),
+                    _ => Ok(Expr::Variable { name, span }),
+                }
+            }
+            TokenKind::OpenParenth => {
+                self.current += 1;
+                let inner = self.parse_expr(0)?;
+                match self.peek() {
+                    Some(Token {
+                        kind: TokenKind::CloseParenth,
+                        span: close,
+                    }) => {
+                        let span = Span::new(span.start, close.end);
+                        self.current += 1;
+                        Ok(Expr::Group {
+                            inner: Box::new(inner),
+                            span,
+                        })
+                    }
+                    Some(Token {
+                        kind: TokenKind::Comma,
+                        span,
+                    }) => Err(Error::Parse(ParseError::UnexpectedComma, *span)),
+                    _ => Err(Error::Parse(
+                        ParseError::ExpectedCloseParenth,
+                        self.end_span(),
+                    )),
+                }
+            }
+            TokenKind::Operator(op @ (Ops::Add | Ops::Subtract)) => {
+                let op = match op {
+                    Ops::Add => UnaryOp::Plus,
+                    _ => UnaryOp::Minus,
+                };
+                self.current += 1;
+                let operand = self.parse_expr(prefix_binding_power())?;
+                Ok(Expr::Unary {
+                    op,
+                    operand: Box::new(operand),
+                    span,
+                })
+            }
+            TokenKind::Tilde => {
+                self.current += 1;
+                let operand = self.parse_expr(prefix_binding_power())?;
+                Ok(Expr::Unary {
+                    op: UnaryOp::BitNot,
+                    operand: Box::new(operand),
+                    span,
+                })
+            }
+            TokenKind::Root => {
+                self.current += 1;
+                let operand = self.parse_expr(prefix_binding_power())?;
+                Ok(Expr::Unary {
+                    op: UnaryOp::Sqrt,
+                    operand: Box::new(operand),
+                    span,
+                })
+            }
+            TokenKind::Bang | TokenKind::DoubleBang => {
+                let double = token.kind == TokenKind::DoubleBang;
+                self.current += 1;
+                let mut operand = self.parse_expr(prefix_binding_power())?;
+                if double {
+                    operand = Expr::Unary {
+                        op: UnaryOp::Not,
+                        operand: Box::new(operand),
+                        span: Span::new(span.start + 1, span.end),
+                    };
+                }
+                Ok(Expr::Unary {
+                    op: UnaryOp::Not,
+                    operand: Box::new(operand),
+                    span: Span::new(span.start, span.start + 1),
+                })
+            }
+            TokenKind::If => {
+                self.current += 1;
+                let condition = self.parse_expr(0)?;
+                self.expect(&TokenKind::Then, ParseError::ExpectedThen)?;
+                let then_branch = self.parse_expr(0)?;
+                self.expect(&TokenKind::Else, ParseError::ExpectedElse)?;
+                let else_branch = self.parse_expr(0)?;
+                Ok(Expr::Conditional {
+                    condition: Box::new(condition),
+                    then_branch: Box::new(then_branch),
+                    else_branch: Box::new(else_branch),
+                    span,
+                })
+            }
+            TokenKind::Then | TokenKind::Else | TokenKind::Colon => {
+                Err(Error::Parse(ParseError::ExpectedRightOperand, span))
+            }
+            TokenKind::Operator(_) | TokenKind::Question => {
+                Err(Error::Parse(ParseError::NotUnaryOperator, span))
+            }
+            TokenKind::CloseParenth => Err(Error::Parse(ParseError::UnexpectedCloseParenth, span)),
+            TokenKind::Assign => Err(Error::Parse(ParseError::UnexpectedAssign, span)),
+            TokenKind::Comma => Err(Error::Parse(ParseError::UnexpectedComma, span)),
+        }
+    }
+    /// Consumes the next token if it is `kind`, or fails with `error` at it.
+    fn expect(&mut self, kind: &TokenKind, error: ParseError) -> Result<(), Error> {
+        match self.peek() {
+            Some(token) if token.kind == *kind => {
+                self.current += 1;
+                Ok(())
+            }
+            Some(token) => Err(Error::Parse(error, token.span)),
+            None => Err(Error::Parse(error, self.end_span())),
+        }
+    }
+    /// Parses `(arg, ...)` after a function name spanning `name_span`.
+    fn parse_call(&mut self, name: String, name_span: Span) -> Result<Expr, Error> {
+        // skip the '('
+        self.current += 1;
+        let mut args = Vec::new();
+        if let Some(Token {
+            kind: TokenKind::CloseParenth,
+            span: close,
+        }) = self.peek()
+        {
+            let span = Span::new(name_span.start, close.end);
+            self.current += 1;
+            return Ok(Expr::Call { name, args, span });
+        }
+        loop {
+            args.push(self.parse_expr(0)?);
+            match self.peek() {
+                Some(Token {
+                    kind: TokenKind::Comma,
+                    ..
+                }) => self.current += 1,
+                Some(Token {
+                    kind: TokenKind::CloseParenth,
+                    span: close,
+                }) => {
+                    let span = Span::new(name_span.start, close.end);
+                    self.current += 1;
+                    return Ok(Expr::Call { name, args, span });
+                }
+                _ => {
+                    return Err(Error::Parse(
+                        ParseError::ExpectedCloseParenth,
+                        self.end_span(),
+                    ))
+                }
+            }
+        }
+    }
+}
+/// Parses a full token stream produced by `math_lexer` into an expression tree.
+pub fn math_parse(tokens: &[Token]) -> Result<Expr, Error> {
+    math_parse_with(tokens, &Config::default())
+}
+/// Like `math_parse`, but honours the operator settings in `config`.
+pub fn math_parse_with(tokens: &[Token], config: &Config) -> Result<Expr, Error> {
+    if tokens.is_empty() {
+        return Err(Error::Parse(ParseError::EmptyExpression, Span::new(0, 0)));
+    }
+    let mut parser = Parser {
+        tokens,
+        config,
+        current: 0,
+        depth: 0,
+    };
+    let expr = parser.parse_expr(0)?;
+    match parser.peek() {
+        Some(Token {
+            kind: TokenKind::Comma,
+            span,
+        }) => Err(Error::Parse(ParseError::UnexpectedComma, *span)),
+        Some(Token {
+            kind: TokenKind::Then | TokenKind::Else | TokenKind::Colon,
+            span,
+        }) => Err(Error::Parse(ParseError::UnmatchedBranch, *span)),
+        Some(token) => Err(Error::Parse(ParseError::UnexpectedCloseParenth, token.span)),
+        None => Ok(expr),
+    }
+}
+/// Parses a line that may be an assignment, `name = expr`, a function
+/// definition, `name(params) = expr`, or a bare expression.
+pub fn math_parse_statement(tokens: &[Token], config: &Config) -> Result<Statement, Error> {
+    if let Some(definition) = parse_definition(tokens, config)? {
+        return Ok(definition);
+    }
+    match tokens {
+        [Token {
+            kind: TokenKind::Ident(name),
+            span,
+        }, Token {
+            kind: TokenKind::Assign,
+            span: assign,
+        }, rest @ ..] => {
+            if rest.is_empty() {
+                return Err(Error::Parse(
+                    ParseError::ExpectedRightOperand,
+                    Span::new(assign.end, assign.end),
+                ));
+            }
+            Ok(Statement::Assign {
+                name: name.clone(),
+                span: *span,
+                value: math_parse_with(rest, config)?,
+            })
+        }
+        _ => math_parse_with(tokens, config).map(Statement::Expr),
+    }
+}
+/// Returns `None` unless `tokens` start with `name(a, b, ...) =`, so calls
+/// such as `f(1)` are left for the expression parser.
+fn parse_definition(tokens: &[Token], config: &Config) -> Result<Option<Statement>, Error> {
+    let [Token {
+        kind: TokenKind::Ident(name),
+        span,
+    }, Token {
+        kind: TokenKind::OpenParenth,
+        ..
+    }, rest @ ..] = tokens
+    else {
+        return Ok(None);
+    };
+    let mut params: Vec<(&String, Span)> = Vec::new();
+    let mut rest = rest;
+    loop {
+        match rest {
+            [Token {
+                kind: TokenKind::CloseParenth,
+                ..
+            }, after @ ..]
+                if params.is_empty() =>
+            {
+                rest = after;
+                break;
+            }
+            [Token {
+                kind: TokenKind::Ident(param),
+                span: param_span,
+            }, separator, after @ ..] => {
+                params.push((param, *param_span));
+                rest = after;
+                match separator.kind {
+                    TokenKind::Comma => {}
+                    TokenKind::CloseParenth => break,
+                    _ => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        }
+    }
+    let [Token {
+        kind: TokenKind::Assign,
+        span: assign,
+    }, body @ ..] = rest
+    else {
+        return Ok(None);
+    };
+    // only now is this a definition rather than a call such as `max(x, x)`
+    for (idx, (param, param_span)) in params.iter().enumerate() {
+        if params[..idx].iter().any(|(earlier, _)| earlier == param) {
+            return Err(Error::Parse(ParseError::DuplicateParameter, *param_span));
+        }
+    }
+    if body.is_empty() {
+        return Err(Error::Parse(
+            ParseError::ExpectedRightOperand,
+            Span::new(assign.end, assign.end),
+        ));
+    }
+    Ok(Some(Statement::Define {
+        name: name.clone(),
+        params: params.into_iter().map(|(param, _)| param.clone()).collect(),
+        body: math_parse_with(body, config)?,
+        span: *span,
+    }))
+}
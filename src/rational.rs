@@ -0,0 +1,200 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::bigint::{BigInt, MAX_DIGITS};
+use crate::error::EvalError;
+
+/// Exact fraction kept in lowest terms with a positive denominator, so equal
+/// values always compare equal field by field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rational {
+    numer: BigInt,
+    denom: BigInt,
+}
+impl Rational {
+    /// Returns `None` when `denom` is zero.
+    pub fn new(numer: BigInt, denom: BigInt) -> Option<Rational> {
+        if denom.is_zero() {
+            return None;
+        }
+        let gcd = numer.gcd(&denom);
+        let (mut numer, _) = numer.div_rem(&gcd).expect("gcd of nonzero denominator");
+        let (mut denom, _) = denom.div_rem(&gcd).expect("gcd of nonzero denominator");
+        if denom.is_negative() {
+            numer = -&numer;
+            denom = -&denom;
+        }
+        Some(Rational { numer, denom })
+    }
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+    pub fn denom(&self) -> &BigInt {
+        &self.denom
+    }
+    pub fn is_integer(&self) -> bool {
+        self.denom == BigInt::from(1)
+    }
+    pub fn to_f64(&self) -> f64 {
+        self.numer.to_f64() / self.denom.to_f64()
+    }
+    pub fn add(&self, other: &Rational) -> Rational {
+        let numer = &(&self.numer * &other.denom) + &(&other.numer * &self.denom);
+        Rational::new(numer, &self.denom * &other.denom).expect("nonzero denominators")
+    }
+    pub fn sub(&self, other: &Rational) -> Rational {
+        self.add(&other.neg())
+    }
+    pub fn mul(&self, other: &Rational) -> Rational {
+        Rational::new(&self.numer * &other.numer, &self.denom * &other.denom)
+            .expect("nonzero denominators")
+    }
+    /// Returns `None` when `other` is zero.
+    pub fn div(&self, other: &Rational) -> Option<Rational> {
+        Rational::new(&self.numer * &other.denom, &self.denom * &other.numer)
+    }
+    pub fn neg(&self) -> Rational {
+        Rational {
+            numer: -&self.numer,
+            denom: self.denom.clone(),
+        }
+    }
+    /// Raises to an integer power; negative powers take the reciprocal.
+    pub fn pow(&self, exp: &Rational) -> Result<Rational, EvalError> {
+        if !exp.is_integer() {
+            return Err(EvalError::NotAnInteger);
+        }
+        let one = BigInt::from(1);
+        if self.numer.is_zero() || (self.is_integer() && self.numer.abs() == one) {
+            if exp.numer.is_negative() && self.numer.is_zero() {
+                return Err(EvalError::DivideByZero);
+            }
+            let (_, parity) = exp
+                .numer
+                .div_rem(&BigInt::from(2))
+                .expect("nonzero divisor");
+            let result = if exp.numer.is_zero() {
+                one
+            } else if parity.is_zero() {
+                self.numer.abs()
+            } else {
+                self.numer.clone()
+            };
+            return Ok(Rational::from(result));
+        }
+        let magnitude = exp
+            .numer
+            .abs()
+            .to_i64()
+            .and_then(|exp| u32::try_from(exp).ok());
+        let size = self.numer.log10_abs().max(self.denom.log10_abs());
+        match magnitude {
+            Some(power) if size * power as f64 <= MAX_DIGITS => {
+                let (numer, denom) = (self.numer.pow(power), self.denom.pow(power));
+                if exp.numer.is_negative() {
+                    Ok(Rational::new(denom, numer).expect("nonzero base"))
+                } else {
+                    Ok(Rational { numer, denom })
+                }
+            }
+            _ => Err(EvalError::Overflow),
+        }
+    }
+    /// The largest integer not above this value.
+    pub fn floor(&self) -> BigInt {
+        let (quotient, remainder) = self
+            .numer
+            .div_rem(&self.denom)
+            .expect("nonzero denominator");
+        if remainder.is_negative() {
+            &quotient - &BigInt::from(1)
+        } else {
+            quotient
+        }
+    }
+    /// The smallest integer not below this value.
+    pub fn ceil(&self) -> BigInt {
+        -&self.neg().floor()
+    }
+    /// The integer part, rounding toward zero.
+    pub fn trunc(&self) -> BigInt {
+        if self.numer.is_negative() {
+            self.ceil()
+        } else {
+            self.floor()
+        }
+    }
+    /// The nearest integer, with halves rounded away from zero.
+    pub fn round(&self) -> BigInt {
+        let half = Rational::new(BigInt::from(1), BigInt::from(2)).expect("nonzero denominator");
+        if self.numer.is_negative() {
+            -&self.neg().add(&half).floor()
+        } else {
+            self.add(&half).floor()
+        }
+    }
+    /// Formats as a mixed number such as `-1 1/2`.
+    pub fn to_mixed_string(&self) -> String {
+        if self.is_integer() {
+            return self.numer.to_string();
+        }
+        let (whole, remainder) = self
+            .numer
+            .div_rem(&self.denom)
+            .expect("nonzero denominator");
+        if whole.is_zero() {
+            return self.to_string();
+        }
+        format!("{} {}/{}", whole, remainder.abs(), self.denom)
+    }
+    /// Formats as a decimal rounded half away from zero to `places` places,
+    /// with trailing zeroes dropped.
+    pub fn to_decimal_string(&self, places: usize) -> String {
+        let scale = BigInt::from(10).pow(places as u32);
+        let scaled = &self.numer.abs() * &scale;
+        let (mut digits, remainder) = scaled.div_rem(&self.denom).expect("nonzero denominator");
+        if (&remainder * &BigInt::from(2)) >= self.denom {
+            digits = &digits + &BigInt::from(1);
+        }
+        let (whole, frac) = digits.div_rem(&scale).expect("nonzero scale");
+        let mut out = String::new();
+        if self.numer.is_negative() && !digits.is_zero() {
+            out.push('-');
+        }
+        out.push_str(&whole.to_string());
+        let frac = format!("{:0>width$}", frac.to_string(), width = places);
+        let frac = frac.trim_end_matches('0');
+        if !frac.is_empty() {
+            out.push('.');
+            out.push_str(frac);
+        }
+        out
+    }
+}
+impl From<BigInt> for Rational {
+    fn from(value: BigInt) -> Rational {
+        Rational {
+            numer: value,
+            denom: BigInt::from(1),
+        }
+    }
+}
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        (&self.numer * &other.denom).cmp(&(&other.numer * &self.denom))
+    }
+}
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
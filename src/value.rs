@@ -0,0 +1,86 @@
+use std::fmt;
+
+use crate::bigint::BigInt;
+use crate::complex::Complex;
+use crate::decimal::Decimal;
+use crate::format::{format_complex, Format};
+use crate::rational::Rational;
+
+/// The result of evaluating an expression. Which numeric variant is produced
+/// depends on the `Mode` in the `Config` used for evaluation; comparisons
+/// and logical operators produce `Bool` in every mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Big(BigInt),
+    Float(f64),
+    Rational(Rational),
+    Complex(Complex),
+    Decimal(Decimal),
+    Bool(bool),
+}
+impl Value {
+    pub(crate) fn to_big(&self) -> BigInt {
+        match self {
+            Value::Int(value) => BigInt::from(*value),
+            Value::Big(value) => value.clone(),
+            Value::Float(_) | Value::Rational(_) | Value::Complex(_) | Value::Decimal(_) => {
+                unreachable!("fractions are never narrowed to integers")
+            }
+            Value::Bool(_) => unreachable!("booleans are rejected before arithmetic"),
+        }
+    }
+    pub(crate) fn to_rational(&self) -> Rational {
+        match self {
+            Value::Rational(value) => value.clone(),
+            Value::Decimal(value) => value.to_rational(),
+            Value::Float(_) | Value::Complex(_) => {
+                unreachable!("floats are never converted to rationals")
+            }
+            _ => Rational::from(self.to_big()),
+        }
+    }
+    pub(crate) fn to_f64(&self) -> f64 {
+        match self {
+            Value::Int(value) => *value as f64,
+            Value::Big(value) => value.to_f64(),
+            Value::Float(value) => *value,
+            Value::Rational(value) => value.to_f64(),
+            Value::Decimal(value) => value.to_f64(),
+            Value::Complex(_) => unreachable!("complex numbers are never narrowed to floats"),
+            Value::Bool(_) => unreachable!("booleans are rejected before arithmetic"),
+        }
+    }
+    pub(crate) fn to_complex(&self) -> Complex {
+        match self {
+            Value::Complex(value) => *value,
+            _ => Complex::new(self.to_f64(), 0.0),
+        }
+    }
+}
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(value) => value.fmt(f),
+            Value::Big(value) => value.fmt(f),
+            Value::Float(value) => value.fmt(f),
+            Value::Rational(value) => value.fmt(f),
+            Value::Complex(value) => f.write_str(&format_complex(value, &Format::default())),
+            Value::Decimal(value) => value.fmt(f),
+            Value::Bool(value) => value.fmt(f),
+        }
+    }
+}
+impl PartialEq<i64> for Value {
+    fn eq(&self, other: &i64) -> bool {
+        match self {
+            Value::Int(value) => value == other,
+            Value::Big(value) => value.to_i64() == Some(*other),
+            Value::Float(value) => *value == *other as f64,
+            Value::Rational(value) => *value == Rational::from(BigInt::from(*other)),
+            Value::Complex(value) => *value == Complex::new(*other as f64, 0.0),
+            Value::Decimal(value) => value.to_rational() == Rational::from(BigInt::from(*other)),
+            Value::Bool(_) => false,
+        }
+    }
+}